@@ -4,42 +4,255 @@ use ink_lang as ink;
 
 #[ink::contract]
 mod crowdfund {
-    use ink_prelude::{string::String, vec::Vec};
-    use ink_storage::{traits::SpreadAllocate, Mapping};
+    use ink_env::call::{build_call, Call, ExecutionInput, Selector};
+    use ink_prelude::{boxed::Box, string::String, vec, vec::Vec};
+    use ink_storage::{
+        traits::{KeyPtr, PackedLayout, SpreadAllocate, SpreadLayout},
+        Mapping,
+    };
 
     const MAX_VOTING_TIME: u64 = 90 * 24 * 60 * 60 * 1000; // 90 days
+    const MAX_DEADLINE_EXTENSION: u64 = 30 * 24 * 60 * 60 * 1000; // 30 days
+    const EXTENSION_GRACE_PERIOD: u64 = 24 * 60 * 60 * 1000; // 1 day, no approval needed
     const MAX_FEE_PERCENT: u8 = 100;
     const MAX_NAME_LENGTH: usize = 50;
     const MAX_DESCRIPTION_LENGTH: usize = 500;
     const MIN_GOAL: u128 = 1;
+    const MAX_MILESTONES: usize = 10;
+    const MAX_REWARD_TIERS: usize = 10;
+    const MAX_STRETCH_GOALS: usize = 10;
+    const MAX_BENEFICIARIES: usize = 10;
+    // Pauses which extend deadlines are kept for good and read by every project lookup.
+    const MAX_EXTENDING_PAUSES: usize = 20;
+    const BASIS_POINTS: u16 = 10_000;
+    // Share of the budget which has to vote yes, a majority at least.
+    const MIN_THRESHOLD: u8 = 50;
+    const MAX_THRESHOLD: u8 = 99;
+    const MAX_QUORUM: u8 = 100;
+
+    fn funding_reached(info: &ProjectInfo, budget: u128) -> bool {
+        // Flexible campaigns keep whatever they raise, the goal is only a target.
+        match info.funding_mode {
+            FundingMode::AllOrNothing => budget >= info.goal,
+            FundingMode::Flexible | FundingMode::FlexibleWithVote => budget > 0,
+        }
+    }
+
+    fn is_co_author(info: &ProjectInfo, account: AccountId) -> bool {
+        account == info.author
+            || info
+                .beneficiaries
+                .iter()
+                .any(|beneficiary| beneficiary.account == account)
+    }
+
+    fn split_payout(info: &ProjectInfo, amount: u128) -> Vec<(AccountId, u128)> {
+        // The last beneficiary takes whatever is left so that no rounding dust remains.
+        if info.beneficiaries.is_empty() {
+            return vec![(info.author, amount)];
+        }
+
+        let mut paid = 0;
+        let mut payouts = Vec::new();
+        for (index, beneficiary) in info.beneficiaries.iter().enumerate() {
+            let share = match index + 1 == info.beneficiaries.len() {
+                true => amount - paid,
+                false => mul_div(amount, beneficiary.share as u128, BASIS_POINTS as u128),
+            };
+            paid += share;
+            payouts.push((beneficiary.account, share));
+        }
+        payouts
+    }
+
+    fn quadratic_weight(info: &ProjectInfo, donated: u128) -> u128 {
+        match info.quadratic_voting {
+            true => isqrt(donated),
+            false => 0,
+        }
+    }
+
+    // Integer square root, rounded down. Newton's method, no floating point in no_std.
+    pub fn isqrt(value: u128) -> u128 {
+        if value < 2 {
+            return value;
+        }
+
+        let mut x = value;
+        let mut y = x / 2 + 1;
+        while y < x {
+            x = y;
+            y = (x + value / x) / 2;
+        }
+        x
+    }
+
+    // value * numerator / denominator rounded down, the product is kept in 256 bits.
+    // Pro-rata splits have numerator <= denominator, the quotient saturates otherwise.
+    pub fn mul_div(value: u128, numerator: u128, denominator: u128) -> u128 {
+        const LOW: u128 = u64::MAX as u128;
+        let (a_high, a_low) = (value >> 64, value & LOW);
+        let (b_high, b_low) = (numerator >> 64, numerator & LOW);
+
+        let low_low = a_low * b_low;
+        let low_high = a_low * b_high;
+        let high_low = a_high * b_low;
+        let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+
+        let low = (low_low & LOW) | (middle << 64);
+        let high = a_high * b_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+        if high == 0 {
+            return low / denominator;
+        }
+        if high >= denominator {
+            return u128::MAX;
+        }
+
+        // Long division of the 256-bit product, the remainder stays below the denominator.
+        let mut remainder = high;
+        let mut quotient = 0;
+        for bit in (0..128).rev() {
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | ((low >> bit) & 1);
+            quotient <<= 1;
+            if carry == 1 || remainder >= denominator {
+                remainder = remainder.wrapping_sub(denominator);
+                quotient |= 1;
+            }
+        }
+        quotient
+    }
+    const STORAGE_VERSION: u32 = 1;
+    const MAX_PAGE_SIZE: u32 = 50;
+    const MAX_DELEGATORS: u32 = 50;
+    const MAX_ROUND_PROJECTS: usize = 50;
+    // Selectors of the PSP22 standard messages.
+    const PSP22_TRANSFER: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+    const PSP22_TRANSFER_FROM: [u8; 4] = [0x54, 0xb3, 0xc7, 0x6e];
+    const PSP34_MINT: [u8; 4] = [0x6c, 0x41, 0xf2, 0xec];
 
     #[derive(scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
     pub enum Error {
+        AllMilestonesReleased,
+        AlreadyApproved,
         AlreadyVoted,
+        BudgetNotClaimedYet,
         CampaignResultUnknown,
         CampaignSuccessfulNoRefunds,
         CampaignUnsuccessfulNoClaims,
         CantDonateOwnProject,
+        ContractNotPaused,
+        ContractPaused,
+        DeadlineAlreadyExtended,
         DeadlineNotPassedYet,
         DeadlinePassed,
         DeadlineTooEarly,
         DescriptionTooLong,
+        DonationTooLarge,
+        DonationTooSmall,
         GoalNotReached,
         GoalTooSmall,
+        IncorrectApprovals,
+        IncorrectBeneficiaries,
+        IncorrectDelegate,
+        IncorrectDonationLimits,
+        IncorrectExtension,
         IncorrectFeePercentage,
+        IncorrectMaxBudget,
+        IncorrectMilestoneShares,
+        IncorrectRewardTiers,
+        IncorrectRound,
+        IncorrectStretchGoals,
+        IncorrectVesting,
+        IncorrectVotingLength,
+        IncorrectVotingRules,
+        IncorrectWithdrawalAmount,
+        MaxBudgetExceeded,
+        MigrationInProgress,
+        MigrationNotNeeded,
+        MilestoneVoteAlreadyStarted,
+        MilestoneVoteNotStarted,
         NameTooLong,
+        NewAuthorDonated,
+        NoDelegation,
         NoFundsDontatedNoVote,
         NoFundsToClaim,
         NoFundsToRefund,
+        NoLegacyDonation,
+        NoMatchedFunds,
+        NoMilestones,
+        NoReward,
+        NoSuchMilestone,
+        NoSuchRound,
+        NoSuchStretchGoal,
         NoSuchVote,
+        NoVesting,
+        NotOnAllowlist,
+        NotOwner,
+        NotPendingAuthor,
+        NotPendingOwner,
         ProjectAlreadyExists,
+        ProjectAlreadyInRound,
+        ProjectCancelled,
         ProjectDoesntExist,
+        ProjectNotPrivate,
+        RewardAlreadyClaimed,
+        RoundEnded,
+        RoundFinalised,
+        RoundNotEnded,
+        RoundNotFinalised,
+        TooManyDelegators,
+        TooManyMilestones,
+        TooManyPauses,
+        TooManyRewardTiers,
         TransferFailed,
+        UpgradeFailed,
+        VestingCancelled,
+        VotingAlreadyDecided,
         VotingDeadlinePassed,
+        VotingNotRequired,
+        WithdrawalsLocked,
+        WithdrawalsNotAllowed,
+        WrongCurrency,
         YouAreNotTheFather,
     }
 
+    // Errors returned by PSP22 token contracts.
+    #[derive(scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    // Token id and errors of PSP34 (NFT) contracts.
+    #[derive(scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub enum PSP34Id {
+        U8(u8),
+        U16(u16),
+        U32(u32),
+        U64(u64),
+        U128(u128),
+        Bytes(Vec<u8>),
+    }
+
+    #[derive(scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub enum PSP34Error {
+        Custom(String),
+        SelfApprove,
+        NotApproved,
+        TokenExists,
+        TokenNotExists,
+        SafeTransferCheckFailed(String),
+    }
+
     #[derive(
         ink_storage::traits::PackedLayout,
         ink_storage::traits::SpreadLayout,
@@ -54,6 +267,41 @@ mod crowdfund {
         pub create_time: Timestamp,
         pub deadline: Timestamp,
         pub goal: u128,
+        // Platform terms at the time of creation, later changes don't apply.
+        pub voting_length: u64,
+        pub fee_percent: u8,
+        // PSP22 token the project is funded with, None for the native currency.
+        pub token: Option<AccountId>,
+        // Perks for donors, minted as NFTs of the PSP34 collection.
+        pub reward_tiers: Vec<RewardTier>,
+        pub reward_collection: Option<AccountId>,
+        // Tranches in which the budget is released, empty if paid out at once.
+        pub milestones: Vec<Milestone>,
+        // Length of the final window before the deadline without withdrawals, None if not allowed.
+        pub withdrawal_lock_in: Option<u64>,
+        pub voting_rules: VotingRules,
+        // Votes weigh as much as the square root of the donation instead of the donation.
+        pub quadratic_voting: bool,
+        pub funding_mode: FundingMode,
+        // Ordered by the amount, each above the goal and the previous one.
+        pub stretch_goals: Vec<StretchGoal>,
+        // Donors vote on every unlocked stretch goal, a rejected one caps the claimable budget.
+        pub stretch_goal_votes: bool,
+        // Reaching it closes the campaign early, None for no cap.
+        pub max_budget: Option<u128>,
+        // Donations over the cap are cut down and the excess sent back instead of rejected.
+        pub refund_excess: bool,
+        // Bounds on the total donated by a single account, None for no bound.
+        pub min_donation: Option<u128>,
+        pub max_donation_per_account: Option<u128>,
+        // Only accounts on the allowlist kept by the author can donate.
+        pub private: bool,
+        // Co-authors sharing the claimed budget in basis points, empty if it all goes to the author.
+        pub beneficiaries: Vec<Beneficiary>,
+        // Co-authors who have to call an author-only action before it happens, 0 or 1 for any single one.
+        pub approvals_required: u8,
+        // Streams the claimed budget to the beneficiaries instead of paying it out at once.
+        pub vesting: Option<VestingSchedule>,
     }
 
     #[derive(
@@ -63,532 +311,8441 @@ mod crowdfund {
         scale::Decode,
     )]
     #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
-    pub struct ProjectVotes {
-        pub ovr_voted_yes: u128,
-        pub ovr_voted_no: u128,
+    pub struct ProjectInfoV0 {
+        // Layout of the project info in storage version 0, converted by `migrate`.
+        pub description: String,
+        pub author: AccountId,
+        pub create_time: Timestamp,
+        pub deadline: Timestamp,
+        pub goal: u128,
     }
 
-    #[ink(storage)]
-    #[derive(SpreadAllocate)]
-    pub struct Crowdfund {
-        // length of voting in milliseconds
-        // global for every project
+    // Contract storage as laid out in storage version 0.
+    // Pulling it gives access to the legacy records under their original keys.
+    #[derive(ink_storage::traits::SpreadLayout)]
+    pub struct LegacyStorage {
         voting_length: u64,
         fee_percent: u8,
         owner_account: AccountId,
-        // Mappings from (project) to ...
-        projects: Mapping<String, ProjectInfo>, // project --> static info about it
-        budgets: Mapping<String, u128>,         // project --> overall collected budget
-        voting_state: Mapping<String, ProjectVotes>, // project --> voting state
-        claimed: Mapping<String, bool>,         // project --> author claimed funds
-        // Mappings from (project, account) to ...
-        donations: Mapping<(String, AccountId), u128>, // project, account --> donated amount
-        votes: Mapping<(String, AccountId), bool>,     // project, account --> has voted
-        refunded: Mapping<(String, AccountId), bool>, // project, account --> account refunded donation to project
-        // All project names
-        project_names: Vec<String>,
+        pub projects: Mapping<String, ProjectInfoV0>,
+        budgets: Mapping<String, u128>,
+        pub voting_state: Mapping<String, ProjectVotesV0>,
+        claimed: Mapping<String, bool>,
+        donations: Mapping<(String, AccountId), u128>,
+        votes: Mapping<(String, AccountId), bool>,
+        refunded: Mapping<(String, AccountId), bool>,
+        // Names in the order of creation, moved to the indexed mapping by `migrate`.
+        pub project_names: Appended<Vec<String>>,
     }
 
-    use ink_lang::utils::initialize_contract;
-    impl Crowdfund {
-        #[ink(constructor)]
-        pub fn new(voting_length: u64, fee_percent: u8, owner_account: AccountId) -> Self {
-            // Truncates invalid values to MAX / MIN value.
-            initialize_contract(|contract: &mut Self| {
-                contract.voting_length = voting_length;
-                if voting_length > MAX_VOTING_TIME {
-                    contract.voting_length = MAX_VOTING_TIME;
-                }
-
-                contract.fee_percent = fee_percent;
-                if fee_percent > MAX_FEE_PERCENT {
-                    contract.fee_percent = MAX_FEE_PERCENT;
-                }
-
-                contract.owner_account = owner_account;
-            })
-        }
-
-        #[ink(message)]
-        pub fn get_static_info(&self) -> Result<(u64, u8, AccountId), Error> {
-            return Ok((self.voting_length, self.fee_percent, self.owner_account));
+    impl LegacyStorage {
+        pub fn pull() -> Self {
+            let root_key = ink_primitives::Key::from([0x00; 32]);
+            ink_storage::traits::pull_spread_root(&root_key)
         }
+    }
 
-        #[ink(message)]
-        pub fn create_project(
-            &mut self,
-            project_name: String,
-            description: String,
-            deadline: Timestamp,
-            goal: u128,
-        ) -> Result<(), Error> {
-            // Verify that no project of the given name exists.
-            if self.projects.contains(project_name.clone()) {
-                return Err(Error::ProjectAlreadyExists);
-            }
-
-            let author = self.env().caller();
-            let create_time = self.env().block_timestamp();
-
-            if create_time >= deadline {
-                return Err(Error::DeadlineTooEarly);
-            }
-
-            if project_name.len() > MAX_NAME_LENGTH {
-                return Err(Error::NameTooLong);
-            }
-
-            if description.len() > MAX_DESCRIPTION_LENGTH {
-                return Err(Error::DescriptionTooLong);
-            }
-
-            if goal < MIN_GOAL {
-                return Err(Error::GoalTooSmall);
-            }
-
-            // Compose immutable project info.
-            let info = ProjectInfo {
-                description,
-                author,
-                create_time,
-                deadline,
-                goal,
-            };
-
-            // Initial voting state (no votes).
-            let voting_state = ProjectVotes {
-                ovr_voted_yes: 0,
-                ovr_voted_no: 0,
-            };
+    // Root field appended to the storage after the first deployment. It reads as the default
+    // until written, so that the upgraded code can load the storage left by the previous one.
+    #[derive(Default)]
+    #[cfg_attr(feature = "std", derive(Debug))]
+    pub struct Appended<T>(T);
 
-            // Initialize the project in storage.
-            self.projects.insert(project_name.clone(), &info);
-            self.budgets.insert(project_name.clone(), &0);
-            self.voting_state
-                .insert(project_name.clone(), &voting_state);
-            self.claimed.insert(project_name.clone(), &false);
-            self.project_names.push(project_name);
-            Ok(())
-        }
+    impl<T> core::ops::Deref for Appended<T> {
+        type Target = T;
 
-        #[ink(message)]
-        pub fn get_project_info(&self, project_name: String) -> Result<ProjectInfo, Error> {
-            match self.projects.get(project_name) {
-                Some(value) => Ok(value),
-                None => Err(Error::ProjectDoesntExist),
-            }
+        fn deref(&self) -> &T {
+            &self.0
         }
+    }
 
-        #[ink(message)]
-        pub fn get_collected_budget(&self, project_name: String) -> Result<u128, Error> {
-            match self.budgets.get(project_name) {
-                Some(value) => Result::Ok(value),
-                None => Err(Error::ProjectDoesntExist),
-            }
+    impl<T> core::ops::DerefMut for Appended<T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.0
         }
+    }
 
-        #[ink(message)]
-        pub fn get_voting_state(&self, project_name: String) -> Result<ProjectVotes, Error> {
-            match self.voting_state.get(project_name) {
-                Some(value) => Ok(value),
-                None => Err(Error::ProjectDoesntExist),
-            }
-        }
+    impl<T: PackedLayout + Default> SpreadLayout for Appended<T> {
+        const FOOTPRINT: u64 = 1;
 
-        #[ink(message)]
-        pub fn get_author_claimed(&self, project_name: String) -> Result<bool, Error> {
-            match self.claimed.get(project_name) {
-                Some(value) => Result::Ok(value),
-                None => Err(Error::ProjectDoesntExist),
+        fn pull_spread(ptr: &mut KeyPtr) -> Self {
+            let key = ptr.advance_by(1);
+            match ink_env::get_contract_storage::<T>(key) {
+                Ok(Some(mut value)) => {
+                    value.pull_packed(key);
+                    Self(value)
+                }
+                Ok(None) => Self(T::default()),
+                Err(_) => panic!("could not properly decode storage entry"),
             }
         }
 
-        #[ink(message)]
-        pub fn get_donated_amount(
-            &self,
-            project_name: String,
-            account: AccountId,
-        ) -> Result<u128, Error> {
-            if !self.projects.contains(project_name.clone()) {
-                return Err(Error::ProjectDoesntExist);
-            }
-
-            Result::Ok(match self.donations.get((project_name, account)) {
-                Some(value) => value,
-                None => 0,
-            })
+        fn push_spread(&self, ptr: &mut KeyPtr) {
+            ink_storage::traits::push_packed_root(&self.0, ptr.advance_by(1));
         }
 
-        #[ink(message)]
-        pub fn get_vote(&self, project_name: String, account: AccountId) -> Result<bool, Error> {
-            if !self.projects.contains(project_name.clone()) {
-                return Err(Error::ProjectDoesntExist);
-            }
-
-            match self.votes.get((project_name, account)) {
-                Some(value) => Ok(value),
-                None => Err(Error::NoSuchVote),
-            }
+        fn clear_spread(&self, ptr: &mut KeyPtr) {
+            ink_storage::traits::clear_packed_root(&self.0, ptr.advance_by(1));
         }
+    }
 
-        #[ink(message)]
-        pub fn get_donor_refunded(
-            &self,
-            project_name: String,
-            account: AccountId,
-        ) -> Result<bool, Error> {
-            if !self.projects.contains(project_name.clone()) {
-                return Err(Error::ProjectDoesntExist);
-            }
-
-            Result::Ok(match self.refunded.get((project_name, account)) {
-                Some(value) => value,
-                None => false,
-            })
+    impl<T: PackedLayout + Default> SpreadAllocate for Appended<T> {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.advance_by(1);
+            Self(T::default())
         }
+    }
 
-        #[ink(message)]
-        pub fn get_all_projects(&self) -> Result<Vec<String>, Error> {
-            Ok(self.project_names.clone())
+    #[cfg(feature = "std")]
+    impl<T: ::scale_info::TypeInfo + 'static> ink_storage::traits::StorageLayout for Appended<T> {
+        fn layout(key_ptr: &mut KeyPtr) -> ink_metadata::layout::Layout {
+            ink_metadata::layout::Layout::Cell(ink_metadata::layout::CellLayout::new::<T>(
+                ink_metadata::layout::LayoutKey::from(key_ptr.advance_by(1)),
+            ))
         }
+    }
 
-        #[ink(message, payable)]
-        pub fn make_donation(&mut self, project_name: String) -> Result<(), Error> {
-            // Fetch project info. It checks if the project exists.
-            let info = match self.get_project_info(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct Milestone {
+        // Percentage of the collected budget released with this milestone.
+        pub share: u8,
+        pub description: String,
+    }
 
-            // Donations after the deadline are not permitted.
-            let current_time = self.env().block_timestamp();
-            if current_time >= info.deadline {
-                return Err(Error::DeadlinePassed);
-            }
+    // Optional settings of a project chosen by the author at creation.
+    // Fields stored as they are in ProjectInfo are documented there.
+    #[derive(scale::Encode, scale::Decode, Default)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct ProjectOptions {
+        // Shares have to sum up to 100, leave empty to release the whole budget at once.
+        pub milestones: Vec<Milestone>,
+        // PSP22 token accepted instead of the native currency.
+        pub token: Option<AccountId>,
+        // Ordered by the minimum amount, the collection has to let this contract mint.
+        pub reward_tiers: Vec<RewardTier>,
+        pub reward_collection: Option<AccountId>,
+        // Lets donors withdraw until the given time before the deadline, None to disallow.
+        pub withdrawal_lock_in: Option<u64>,
+        // Have to be within the bounds set by the owner, None for the platform defaults.
+        pub voting_rules: Option<VotingRules>,
+        pub quadratic_voting: bool,
+        pub funding_mode: FundingMode,
+        pub stretch_goals: Vec<StretchGoal>,
+        pub stretch_goal_votes: bool,
+        pub max_budget: Option<u128>,
+        pub refund_excess: bool,
+        pub min_donation: Option<u128>,
+        pub max_donation_per_account: Option<u128>,
+        pub private: bool,
+        pub beneficiaries: Vec<Beneficiary>,
+        pub approvals_required: u8,
+        pub vesting: Option<VestingSchedule>,
+    }
 
-            // Assuming the author can't donate to their own project.
-            let donor = self.env().caller();
-            if donor == info.author {
-                return Err(Error::CantDonateOwnProject);
-            }
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct Beneficiary {
+        pub account: AccountId,
+        // Part of every claimed tranche, in basis points.
+        pub share: u16,
+    }
 
-            // Fetch the already donated amount.
-            let donated = match self.get_donated_amount(project_name.clone(), donor) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+        Copy,
+        PartialEq,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug))]
+    pub enum AuthorAction {
+        CancelProject,
+        ExtendDeadline(u64),
+        UpdateAllowlist(AccountId, bool),
+        TransferProject(AccountId),
+    }
 
-            // Fetch the transferred value and the project's collected budget.
-            let value = self.env().transferred_value();
-            let budget = match self.get_collected_budget(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct RewardTier {
+        // Donated amount needed to qualify for the tier.
+        pub min_amount: u128,
+        // Maximum number of donors in the tier.
+        pub supply: u32,
+        pub uri: String,
+    }
 
-            // Make note of the donation and update the collected budget.
-            self.donations
-                .insert((project_name.clone(), donor), &(donated + value));
-            self.budgets.insert(project_name, &(budget + value));
-            Ok(())
-        }
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct StretchGoal {
+        // Budget which unlocks the stretch goal.
+        pub amount: u128,
+        pub description: String,
+    }
 
-        #[ink(message)]
-        pub fn make_vote(&mut self, project_name: String, vote: bool) -> Result<(), Error> {
-            // Fetch project info. It checks if the project exists.
-            let info = match self.get_project_info(project_name.clone()) {
-                // also checks if project exists
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct ProjectVotes {
+        pub ovr_voted_yes: u128,
+        pub ovr_voted_no: u128,
+        // Sums of square roots of the donations, only kept for quadratic voting.
+        pub quad_voted_yes: u128,
+        pub quad_voted_no: u128,
+    }
 
-            // Voting before the project's deadline is not permitted.
-            let current_time = self.env().block_timestamp();
-            if current_time < info.deadline {
-                return Err(Error::DeadlineNotPassedYet);
-            }
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct ProjectVotesV0 {
+        // Layout of the voting state in storage version 0, converted by `migrate`.
+        pub ovr_voted_yes: u128,
+        pub ovr_voted_no: u128,
+    }
 
-            // Voting after the voting deadline is not permitted
-            if current_time > info.deadline + self.voting_length {
-                return Err(Error::VotingDeadlinePassed);
-            }
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct VestingSchedule {
+        // Time the stream starts at, not before the deadline.
+        pub start: Timestamp,
+        // Nothing vests until this long after the start.
+        pub cliff: u64,
+        // The whole budget vests linearly over this time from the start.
+        pub duration: u64,
+    }
 
-            let budget = match self.get_collected_budget(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct VotingRules {
+        // Percent of the budget which has to vote yes, abstaining counts as no.
+        pub threshold: u8,
+        // Percent of the budget which has to vote at all for the result to count.
+        pub quorum: u8,
+        // Result of the voting which missed the quorum.
+        pub default_outcome: bool,
+    }
 
-            // Voting is needed only if the goal of the project was reached.
-            if budget < info.goal {
-                return Err(Error::GoalNotReached);
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct VotingBounds {
+        // Voting rules the authors can choose from, inclusive.
+        pub min_threshold: u8,
+        pub max_threshold: u8,
+        pub min_quorum: u8,
+        pub max_quorum: u8,
+        pub default_success_allowed: bool,
+    }
+
+    impl Default for VotingBounds {
+        // The platform defaults, also for deployments from before the bounds existed.
+        fn default() -> Self {
+            VotingBounds {
+                min_threshold: MIN_THRESHOLD,
+                max_threshold: MAX_THRESHOLD,
+                min_quorum: 0,
+                max_quorum: MAX_QUORUM,
+                default_success_allowed: true,
             }
+        }
+    }
 
-            let account = self.env().caller();
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct MatchingRound {
+        // Donations made in [start, end) are matched from the round's pool.
+        pub start: Timestamp,
+        pub end: Timestamp,
+        // Currency of the pool, the same as of all the projects in the round.
+        pub token: Option<AccountId>,
+        pub projects: Vec<String>,
+    }
 
-            match self.get_vote(project_name.clone(), account) {
-                // Ok means that a vote from this account corresponding to this project was recorded. Duplicate votes are not permitted.
-                Ok(_) => return Err(Error::AlreadyVoted),
-                // Project exists so the following error must be NoSuchVote. This is expected.
-                Err(_) => (),
-            };
+    #[derive(
+        ink_storage::traits::PackedLayout,
+        ink_storage::traits::SpreadLayout,
+        scale::Encode,
+        scale::Decode,
+        Clone,
+        Default,
+    )]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub enum FundingMode {
+        // The budget is paid out only if the goal was reached and the donors approve.
+        #[default]
+        AllOrNothing,
+        // Whatever was raised is paid out after the deadline.
+        Flexible,
+        // Whatever was raised is paid out after the deadline if the donors approve.
+        FlexibleWithVote,
+    }
 
-            let donated = match self.get_donated_amount(project_name.clone(), account) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub enum ProjectStatus {
+        // Collecting donations before the deadline.
+        Active,
+        // Waiting for the donors to decide on the (next part of the) budget.
+        Voting,
+        // The author may claim the budget.
+        Succeeded,
+        // The donors may refund their donations.
+        Failed,
+        Cancelled,
+        // The whole budget was released to the author.
+        Claimed,
+    }
 
-            // No donation, no vote.
-            if donated <= 0 {
-                return Err(Error::NoFundsDontatedNoVote);
-            }
+    #[derive(scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct DonationSummary {
+        pub project_name: String,
+        pub amount: u128,
+        pub refunded: bool,
+        // None if the donor didn't vote (yet).
+        pub vote: Option<bool>,
+    }
 
-            let mut voting_state = match self.get_voting_state(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[derive(scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, Debug, PartialEq))]
+    pub struct ProjectSummary {
+        pub project_name: String,
+        pub info: ProjectInfo,
+        pub budget: u128,
+        pub votes: ProjectVotes,
+    }
 
-            // Update the corresponding weighted sum of votes according to the vote.
-            match vote {
-                true => voting_state.ovr_voted_yes += donated,
-                false => voting_state.ovr_voted_no += donated,
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct ProjectCreated {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub author: AccountId,
+        pub deadline: Timestamp,
+        pub goal: u128,
+    }
 
-            // Override the voting state in storage & make note of the vote.
-            self.voting_state
-                .insert(project_name.clone(), &voting_state);
-            self.votes.insert((project_name, account), &vote);
-            Ok(())
-        }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct ProjectCancelled {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub author: AccountId,
+    }
 
-        #[ink(message)]
-        pub fn get_project_voting_result(&self, project_name: String) -> Result<bool, Error> {
-            let info = match self.get_project_info(project_name.clone()) {
-                // also checks if project exists
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct ActionApproved {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub account: AccountId,
+        pub action: AuthorAction,
+        pub approvals: u32,
+    }
 
-            let budget = match self.get_collected_budget(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct AllowlistChanged {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub account: AccountId,
+        pub allowed: bool,
+    }
 
-            let voting_state = match self.get_voting_state(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct DonationMade {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub amount: u128,
+    }
 
-            if 2 * voting_state.ovr_voted_yes > budget {
-                return Ok(true);
-            }
-            if 2 * voting_state.ovr_voted_no >= budget {
-                return Ok(false);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct MaxBudgetReached {
+        #[ink(topic)]
+        pub project_name: String,
+        pub budget: u128,
+        pub closed_at: Timestamp,
+    }
 
-            // We treat reaching the deadline as a negative result
-            if info.deadline + self.voting_length < self.env().block_timestamp() {
-                return Ok(false);
-            }
-            return Err(Error::CampaignResultUnknown);
-        }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct DonationWithdrawn {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub amount: u128,
+    }
 
-        #[ink(message)]
-        pub fn refund_donation(&mut self, project_name: String) -> Result<(), Error> {
-            // Fetch project info. It checks if the project exists.
-            let info = match self.get_project_info(project_name.clone()) {
-                // also checks if project exists
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VoteCast {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub vote: bool,
+        pub weight: u128,
+    }
 
-            // Making a refund is only possible after the deadline has passed.
-            let current_time = self.env().block_timestamp();
-            if current_time < info.deadline {
-                return Err(Error::DeadlineNotPassedYet);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VoteChanged {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        // None if the vote was withdrawn.
+        pub vote: Option<bool>,
+        pub weight: u128,
+    }
 
-            let donor = self.env().caller();
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VoteDelegated {
+        // None if the delegation applies to all projects.
+        pub project_name: Option<String>,
+        #[ink(topic)]
+        pub donor: AccountId,
+        // None if the delegation was revoked.
+        #[ink(topic)]
+        pub delegate: Option<AccountId>,
+    }
 
-            // Verify if already refunded it.
-            match self.get_donor_refunded(project_name.clone(), donor) {
-                Ok(value) => match value {
-                    true => return Err(Error::NoFundsToRefund),
-                    false => (),
-                },
-                Err(error) => return Err(error),
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct MilestoneVoteStarted {
+        #[ink(topic)]
+        pub project_name: String,
+        pub milestone: u32,
+    }
 
-            let budget = match self.get_collected_budget(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct MilestoneVoteCast {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub milestone: u32,
+        pub vote: bool,
+        pub weight: u128,
+    }
 
-            // If the goal was reached then refunds are possible only if the voting indicates it.
-            if budget >= info.goal {
-                match self.get_project_voting_result(project_name.clone()) {
-                    Ok(true) => return Err(Error::CampaignSuccessfulNoRefunds),
-                    Ok(false) => (),
-                    Err(error) => return Err(error),
-                }
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct DeadlineExtensionProposed {
+        #[ink(topic)]
+        pub project_name: String,
+        pub extension: u64,
+    }
 
-            let donated = match self.get_donated_amount(project_name.clone(), donor) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct ExtensionVoteCast {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub vote: bool,
+        pub weight: u128,
+    }
 
-            // No donation, no refund
-            if donated <= 0 {
-                return Err(Error::NoFundsToRefund);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct DeadlineExtended {
+        #[ink(topic)]
+        pub project_name: String,
+        pub deadline: Timestamp,
+    }
 
-            // All conditions to make a refund are met.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct StretchVoteCast {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub stretch_goal: u32,
+        pub vote: bool,
+        pub weight: u128,
+    }
 
-            // Make note of the refund
-            self.refunded.insert((project_name.clone(), donor), &true);
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct RefundIssued {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub amount: u128,
+    }
 
-            // Transfer the refund.
-            match self.env().transfer(donor, donated) {
-                Ok(_) => Ok(()),
-                Err(_) => Err(Error::TransferFailed),
-            }
-        }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct BudgetClaimed {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub author: AccountId,
+        pub amount: u128,
+    }
 
-        #[ink(message)]
-        pub fn claim_budget(&mut self, project_name: String) -> Result<(), Error> {
-            // Fetch project info. It checks if the project exists.
-            let info = match self.get_project_info(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VestingStarted {
+        #[ink(topic)]
+        pub project_name: String,
+        pub amount: u128,
+    }
 
-            // Claiming the collected budget is only possible after the deadline.
-            let current_time = self.env().block_timestamp();
-            if current_time < info.deadline {
-                return Err(Error::DeadlineNotPassedYet);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VestingVoteCast {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub vote: bool,
+        pub weight: u128,
+    }
 
-            let author = self.env().caller();
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VestingCancelled {
+        #[ink(topic)]
+        pub project_name: String,
+        pub unvested: u128,
+    }
 
-            // Only the author of the project can claim the budget.
-            if author != info.author {
-                return Err(Error::YouAreNotTheFather);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct FeePaid {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub owner: AccountId,
+        pub amount: u128,
+    }
 
-            let budget = match self.get_collected_budget(project_name.clone()) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct RewardMinted {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub tier: u32,
+    }
 
-            // The campaign can be successful only if the goal was reached.
-            if budget < info.goal {
-                return Err(Error::GoalNotReached);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct RoundCreated {
+        #[ink(topic)]
+        pub round: u32,
+        pub start: Timestamp,
+        pub end: Timestamp,
+        pub projects: Vec<String>,
+    }
 
-            // Verify if already claimed.
-            match self.get_author_claimed(project_name.clone()) {
-                Ok(value) => match value {
-                    true => return Err(Error::NoFundsToClaim),
-                    false => (),
-                },
-                Err(error) => return Err(error),
-            };
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct RoundFunded {
+        #[ink(topic)]
+        pub round: u32,
+        #[ink(topic)]
+        pub sponsor: AccountId,
+        pub amount: u128,
+    }
 
-            // The budget can be claimed by the author only if the voting indicates it.
-            match self.get_project_voting_result(project_name.clone()) {
-                Ok(true) => (),
-                Ok(false) => return Err(Error::CampaignUnsuccessfulNoClaims),
-                Err(error) => return Err(error),
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct RoundFinalised {
+        #[ink(topic)]
+        pub round: u32,
+        pub matched: u128,
+    }
 
-            // No budget, no claim.
-            if budget <= 0 {
-                return Err(Error::NoFundsToClaim);
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct MatchingReturned {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub round: u32,
+        pub amount: u128,
+    }
 
-            // All conditions to claim were met.
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct PoolWithdrawn {
+        #[ink(topic)]
+        pub round: u32,
+        pub owner: AccountId,
+        pub amount: u128,
+    }
 
-            // Make note of the claim.
-            self.claimed.insert(project_name, &true);
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct TermsChanged {
+        pub voting_length: u64,
+        pub fee_percent: u8,
+    }
 
-            // calculate and transfer fee
-            let fee = budget * self.fee_percent as u128 / 100;
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct VotingBoundsChanged {
+        pub bounds: VotingBounds,
+    }
 
-            match self.env().transfer(self.owner_account, fee) {
-                Ok(_) => (),
-                Err(_) => return Err(Error::TransferFailed),
-            }
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct ProjectTransferProposed {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub author: AccountId,
+        #[ink(topic)]
+        pub pending_author: AccountId,
+    }
 
-            // Transfer the claim.
-            match self.env().transfer(author, budget - fee) {
-                Ok(_) => Ok(()),
-                Err(_) => Err(Error::TransferFailed),
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct ProjectTransferred {
+        #[ink(topic)]
+        pub project_name: String,
+        #[ink(topic)]
+        pub previous_author: AccountId,
+        #[ink(topic)]
+        pub author: AccountId,
+    }
+
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct OwnershipProposed {
+        #[ink(topic)]
+        pub owner: AccountId,
+        #[ink(topic)]
+        pub pending_owner: AccountId,
+    }
+
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        pub previous_owner: AccountId,
+        #[ink(topic)]
+        pub owner: AccountId,
+    }
+
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct Paused {
+        pub extend_deadlines: bool,
+    }
+
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct Unpaused {
+        pub paused_time: u64,
+    }
+
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct CodeUpgraded {
+        pub code_hash: [u8; 32],
+    }
+
+    #[ink(event)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+    pub struct Migrated {
+        pub storage_version: u32,
+    }
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Crowdfund {
+        // The terms and voting bounds apply to projects created from now on,
+        // the running ones keep what they were created with.
+        // length of voting in milliseconds
+        voting_length: u64,
+        fee_percent: u8,
+        owner_account: AccountId,
+        // Mappings from (project) to ...
+        projects: Mapping<String, ProjectInfo>, // project --> static info about it
+        budgets: Mapping<String, u128>,         // project --> overall collected budget
+        voting_state: Mapping<String, ProjectVotes>, // project --> voting state
+        claimed: Mapping<String, bool>,         // project --> author claimed funds
+        // Mappings from (project, account) to ...
+        donations: Mapping<(String, AccountId), u128>, // project, account --> donated amount
+        votes: Mapping<(String, AccountId), bool>,     // project, account --> has voted
+        refunded: Mapping<(String, AccountId), bool>, // project, account --> account refunded donation to project
+        // All project names in the order of creation
+        project_names: Mapping<u32, String>, // index --> project
+        // New fields are appended below so that the storage keys of the existing ones
+        // stay the same across code upgrades.
+        // Plain values are wrapped in Appended, as they are missing from older deployments.
+        // account which has to accept the ownership before it's handed over
+        pending_owner: Appended<Option<AccountId>>,
+        // circuit breaker, blocks everything but refunds and votes
+        paused: Appended<bool>,
+        paused_since: Appended<Timestamp>,
+        extend_deadlines: Appended<bool>,
+        // finished pauses which extend deadlines: start, end
+        deadline_extensions: Appended<Vec<(Timestamp, Timestamp)>>,
+        released: Mapping<String, u128>, // project --> budget already released to the author
+        milestone_index: Mapping<String, u32>, // project --> next milestone to be released
+        milestone_voting_start: Mapping<(String, u32), Timestamp>, // project, milestone --> start of the voting
+        milestone_voting_state: Mapping<(String, u32), ProjectVotes>, // project, milestone --> voting state
+        milestone_votes: Mapping<(String, u32, AccountId), bool>, // project, milestone, account --> has voted
+        // layout version of the stored records, behind STORAGE_VERSION until `migrate` finishes
+        storage_version: Appended<u32>,
+        migrated_projects: Appended<u32>, // projects converted so far by the ongoing migration
+        project_count: Appended<u32>,
+        author_projects: Mapping<(AccountId, u32), String>, // author, index --> project
+        author_project_count: Mapping<AccountId, u32>,      // author --> number of projects
+        donor_projects: Mapping<(AccountId, u32), String>,  // donor, index --> project
+        donor_project_count: Mapping<AccountId, u32>,       // donor --> number of projects
+        reward_tier: Mapping<(String, AccountId), u32>, // project, account --> reserved reward tier
+        reward_minted: Mapping<(String, AccountId), bool>, // project, account --> reward NFT minted
+        reward_tier_taken: Mapping<(String, u32), u32>, // project, tier --> donors in the tier
+        cancelled: Mapping<String, bool>,               // project --> cancelled by the author
+        withdrawn: Mapping<(String, AccountId), u128>,  // project, donor --> withdrawn amount
+        deadline_extension: Mapping<String, u64>,       // project --> approved extension
+        extension_proposal: Mapping<String, u64>,       // project --> extension awaiting approval
+        extension_voting_state: Mapping<String, ProjectVotes>, // project --> extension votes
+        extension_votes: Mapping<(String, AccountId), (bool, u128)>, // project, donor --> vote, weight
+        voting_bounds: Appended<VotingBounds>, // limits for the voting rules of new projects
+        // Delegations are kept per project, or for all projects under None.
+        delegates: Mapping<(Option<String>, AccountId), AccountId>, // project, donor --> delegate
+        delegators: Mapping<(Option<String>, AccountId, u32), AccountId>, // project, delegate, index --> donor
+        delegator_count: Mapping<(Option<String>, AccountId), u32>, // project, delegate --> number of donors
+        delegator_position: Mapping<(Option<String>, AccountId), u32>, // project, donor --> index at the delegate
+        delegated_votes: Mapping<(String, AccountId), AccountId>, // project, donor --> delegate who voted with the weight
+        vote_weights: Mapping<(String, AccountId), (u128, u128)>, // project, account --> weight of the vote, quadratic weight
+        quadratic_budgets: Mapping<String, u128>, // project --> sum of square roots of the donations
+        round_count: Appended<u32>,
+        rounds: Mapping<u32, MatchingRound>,
+        round_pools: Mapping<u32, u128>, // round --> funds not matched to the projects
+        round_scores: Mapping<u32, u128>, // round --> sum of the projects' quadratic funding scores
+        round_progress: Mapping<u32, u32>, // round --> finalisation steps done, two per project
+        round_matched: Mapping<u32, u128>, // round --> funds matched so far by the finalisation
+        project_round: Mapping<String, u32>, // project --> matching round
+        matching_contributions: Mapping<(String, AccountId), u128>, // project, donor --> donated during the round
+        matching_sqrt_sums: Mapping<String, u128>, // project --> sum of square roots of the contributions
+        matching_totals: Mapping<String, u128>,    // project --> sum of the contributions
+        matching_donors: Mapping<String, u32>,     // project --> unique donors during the round
+        matched: Mapping<String, u128>, // project --> matched amount paid out with the last tranche
+        stretch_voting_state: Mapping<(String, u32), ProjectVotes>, // project, stretch goal --> voting state
+        stretch_votes: Mapping<(String, u32, AccountId), bool>, // project, stretch goal, account --> vote
+        stretch_refunded: Mapping<(String, AccountId), bool>, // project, account --> portion above the cap refunded
+        closed: Mapping<String, Timestamp>, // project --> time the max budget was reached
+        allowlist: Mapping<(String, AccountId), ()>, // project, account --> allowed to donate to a private project
+        approvals: Mapping<(String, AuthorAction), Vec<AccountId>>, // project, action --> co-authors who approved it
+        pending_authors: Mapping<String, AccountId>, // project --> account the author role is offered to
+        author_project_position: Mapping<String, u32>, // project --> index in its author's list
+        vesting_total: Mapping<String, u128>,        // project --> amount streamed since the claim
+        vesting_released: Mapping<String, u128>,     // project --> amount released from the stream
+        vesting_cancelled: Mapping<String, Timestamp>, // project --> time the donors stopped the stream
+        vesting_voting_state: Mapping<String, ProjectVotes>, // project --> votes on stopping the stream
+        vesting_votes: Mapping<(String, AccountId), bool>, // project, account --> vote on stopping the stream
+        approved_actions: Mapping<String, Vec<AuthorAction>>, // project --> actions with approvals pending
+        vesting_started: Mapping<String, Timestamp>, // project --> start of the stream, not before the claim
+        vesting_matched: Mapping<String, u128>, // project --> part of the stream matched by a round
+        refund_issued: Mapping<String, ()>, // project --> a donor got a refund, the votes can't change
+        legacy_projects: Mapping<String, ()>, // project --> created before the upgrade
+        legacy_indexed: Mapping<(String, AccountId), ()>, // project, donor --> legacy donation indexed
+    }
+
+    use ink_lang::utils::initialize_contract;
+    impl Crowdfund {
+        #[ink(constructor)]
+        pub fn new(voting_length: u64, fee_percent: u8, owner_account: AccountId) -> Self {
+            // Truncates invalid values to MAX / MIN value.
+            initialize_contract(|contract: &mut Self| {
+                contract.voting_length = voting_length;
+                if voting_length > MAX_VOTING_TIME {
+                    contract.voting_length = MAX_VOTING_TIME;
+                }
+
+                contract.fee_percent = fee_percent;
+                if fee_percent > MAX_FEE_PERCENT {
+                    contract.fee_percent = MAX_FEE_PERCENT;
+                }
+
+                contract.owner_account = owner_account;
+                *contract.storage_version = STORAGE_VERSION;
+            })
+        }
+
+        #[ink(message)]
+        pub fn get_static_info(&self) -> Result<(u64, u8, AccountId), Error> {
+            Ok((self.voting_length, self.fee_percent, self.owner_account))
+        }
+
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Result<Option<AccountId>, Error> {
+            Ok(*self.pending_owner)
+        }
+
+        #[ink(message)]
+        pub fn set_voting_length(&mut self, voting_length: u64) -> Result<(), Error> {
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            // The legacy projects take these terms when they're migrated.
+            if *self.storage_version < STORAGE_VERSION {
+                return Err(Error::MigrationInProgress);
+            }
+
+            if voting_length > MAX_VOTING_TIME {
+                return Err(Error::IncorrectVotingLength);
+            }
+
+            self.voting_length = voting_length;
+
+            self.env().emit_event(TermsChanged {
+                voting_length,
+                fee_percent: self.fee_percent,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn set_fee_percent(&mut self, fee_percent: u8) -> Result<(), Error> {
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if *self.storage_version < STORAGE_VERSION {
+                return Err(Error::MigrationInProgress);
+            }
+
+            if fee_percent > MAX_FEE_PERCENT {
+                return Err(Error::IncorrectFeePercentage);
+            }
+
+            self.fee_percent = fee_percent;
+
+            self.env().emit_event(TermsChanged {
+                voting_length: self.voting_length,
+                fee_percent,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_voting_bounds(&self) -> Result<VotingBounds, Error> {
+            Ok(self.voting_bounds.clone())
+        }
+
+        #[ink(message)]
+        pub fn set_voting_bounds(&mut self, bounds: VotingBounds) -> Result<(), Error> {
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if *self.storage_version < STORAGE_VERSION {
+                return Err(Error::MigrationInProgress);
+            }
+
+            if bounds.min_threshold < MIN_THRESHOLD
+                || bounds.min_threshold > bounds.max_threshold
+                || bounds.max_threshold > MAX_THRESHOLD
+                || bounds.min_quorum > bounds.max_quorum
+                || bounds.max_quorum > MAX_QUORUM
+            {
+                return Err(Error::IncorrectVotingRules);
+            }
+
+            *self.voting_bounds = bounds.clone();
+
+            self.env().emit_event(VotingBoundsChanged { bounds });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn propose_owner(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            // The ownership is handed over only when the new owner accepts it.
+            let owner = self.env().caller();
+            if owner != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            *self.pending_owner = Some(new_owner);
+
+            self.env().emit_event(OwnershipProposed {
+                owner,
+                pending_owner: new_owner,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<(), Error> {
+            let new_owner = self.env().caller();
+            if *self.pending_owner != Some(new_owner) {
+                return Err(Error::NotPendingOwner);
+            }
+
+            let previous_owner = self.owner_account;
+            self.owner_account = new_owner;
+            *self.pending_owner = None;
+
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                owner: new_owner,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> Result<u32, Error> {
+            Ok(*self.storage_version)
+        }
+
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: [u8; 32]) -> Result<(), Error> {
+            // The new code has to keep the storage layout, records are converted by `migrate`.
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            match ink_env::set_code_hash(&code_hash) {
+                Ok(_) => (),
+                Err(_) => return Err(Error::UpgradeFailed),
+            }
+
+            self.env().emit_event(CodeUpgraded { code_hash });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn migrate(&mut self, limit: u32) -> Result<(), Error> {
+            // Converts the records of at most `limit` projects, call until the version is bumped.
+            // Projects which weren't converted yet can't be read in the meantime.
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if *self.storage_version >= STORAGE_VERSION {
+                return Err(Error::MigrationNotNeeded);
+            }
+
+            // The names and the authors' lists are indexed along with the conversion,
+            // the count is set once it's done.
+            let legacy = LegacyStorage::pull();
+            let project_count = legacy.project_names.len() as u32;
+            let start = *self.migrated_projects;
+            let end = core::cmp::min(start.saturating_add(limit), project_count);
+
+            for index in start..end {
+                let project_name = &legacy.project_names[index as usize];
+                self.project_names.insert(index, project_name);
+
+                let old = match legacy.projects.get(project_name) {
+                    Some(value) => value,
+                    None => continue,
+                };
+
+                // Projects from before the upgrade keep the deployment's terms, which are locked
+                // until the migration is done, and have no milestones.
+                let info = ProjectInfo {
+                    description: old.description,
+                    author: old.author,
+                    create_time: old.create_time,
+                    deadline: old.deadline,
+                    goal: old.goal,
+                    voting_length: self.voting_length,
+                    fee_percent: self.fee_percent,
+                    token: None,
+                    reward_tiers: Vec::new(),
+                    reward_collection: None,
+                    milestones: Vec::new(),
+                    withdrawal_lock_in: None,
+                    voting_rules: VotingRules {
+                        threshold: 50,
+                        quorum: 0,
+                        default_outcome: false,
+                    },
+                    quadratic_voting: false,
+                    funding_mode: FundingMode::AllOrNothing,
+                    stretch_goals: Vec::new(),
+                    stretch_goal_votes: false,
+                    max_budget: None,
+                    refund_excess: false,
+                    min_donation: None,
+                    max_donation_per_account: None,
+                    private: false,
+                    beneficiaries: Vec::new(),
+                    approvals_required: 0,
+                    vesting: None,
+                };
+
+                // Voting states get the quadratic tallies, quadratic voting wasn't available before.
+                if let Some(old) = legacy.voting_state.get(project_name) {
+                    let voting_state = ProjectVotes {
+                        ovr_voted_yes: old.ovr_voted_yes,
+                        ovr_voted_no: old.ovr_voted_no,
+                        quad_voted_yes: 0,
+                        quad_voted_no: 0,
+                    };
+                    self.voting_state.insert(project_name, &voting_state);
+                }
+
+                // Budgets keep their layout, the release progress is derived from them.
+                let budget = self.budgets.get(project_name).unwrap_or_default();
+                let claimed = self.claimed.get(project_name).unwrap_or_default();
+
+                self.projects.insert(project_name, &info);
+                let authored = self
+                    .author_project_count
+                    .get(info.author)
+                    .unwrap_or_default();
+                self.author_projects
+                    .insert((info.author, authored), project_name);
+                self.author_project_count
+                    .insert(info.author, &(authored + 1));
+                self.author_project_position.insert(project_name, &authored);
+                // The donors can't be listed, they add the project to their index themselves.
+                self.legacy_projects.insert(project_name, &());
+
+                match claimed {
+                    true => {
+                        self.released.insert(project_name, &budget);
+                        self.milestone_index.insert(project_name, &1);
+                    }
+                    false => {
+                        self.released.insert(project_name, &0);
+                        self.milestone_index.insert(project_name, &0);
+                    }
+                }
+            }
+
+            *self.migrated_projects = end;
+            if end == project_count {
+                *self.project_count = project_count;
+                *self.storage_version = STORAGE_VERSION;
+                *self.migrated_projects = 0;
+
+                self.env().emit_event(Migrated {
+                    storage_version: STORAGE_VERSION,
+                });
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_paused(&self) -> Result<bool, Error> {
+            Ok(*self.paused)
+        }
+
+        #[ink(message)]
+        pub fn pause(&mut self, extend_deadlines: bool) -> Result<(), Error> {
+            // Stops new projects, donations and claims until the owner unpauses the contract.
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            if extend_deadlines && self.deadline_extensions.len() >= MAX_EXTENDING_PAUSES {
+                return Err(Error::TooManyPauses);
+            }
+
+            *self.paused = true;
+            *self.paused_since = self.env().block_timestamp();
+            *self.extend_deadlines = extend_deadlines;
+
+            self.env().emit_event(Paused { extend_deadlines });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if !*self.paused {
+                return Err(Error::ContractNotPaused);
+            }
+
+            let current_time = self.env().block_timestamp();
+            if *self.extend_deadlines {
+                self.deadline_extensions
+                    .push((*self.paused_since, current_time));
+            }
+
+            *self.paused = false;
+            *self.extend_deadlines = false;
+
+            self.env().emit_event(Unpaused {
+                paused_time: current_time - *self.paused_since,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn create_project(
+            &mut self,
+            project_name: String,
+            description: String,
+            deadline: Timestamp,
+            goal: u128,
+            options: Box<ProjectOptions>,
+        ) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            // New projects would take the indices of the ones still being migrated.
+            if *self.storage_version < STORAGE_VERSION {
+                return Err(Error::MigrationInProgress);
+            }
+
+            // Verify that no project of the given name exists.
+            if self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectAlreadyExists);
+            }
+
+            let author = self.env().caller();
+            let create_time = self.env().block_timestamp();
+
+            if create_time >= deadline {
+                return Err(Error::DeadlineTooEarly);
+            }
+
+            if project_name.len() > MAX_NAME_LENGTH {
+                return Err(Error::NameTooLong);
+            }
+
+            if description.len() > MAX_DESCRIPTION_LENGTH {
+                return Err(Error::DescriptionTooLong);
+            }
+
+            if goal < MIN_GOAL {
+                return Err(Error::GoalTooSmall);
+            }
+
+            // Milestones, if any, have to split the whole budget.
+            if options.milestones.len() > MAX_MILESTONES {
+                return Err(Error::TooManyMilestones);
+            }
+
+            let mut shares: u32 = 0;
+            for milestone in options.milestones.iter() {
+                if milestone.share == 0 {
+                    return Err(Error::IncorrectMilestoneShares);
+                }
+                if milestone.description.len() > MAX_DESCRIPTION_LENGTH {
+                    return Err(Error::DescriptionTooLong);
+                }
+                shares += milestone.share as u32;
+            }
+
+            if !options.milestones.is_empty() && shares != 100 {
+                return Err(Error::IncorrectMilestoneShares);
+            }
+
+            // Reward tiers need a collection to mint from and have to get more expensive.
+            if options.reward_tiers.len() > MAX_REWARD_TIERS {
+                return Err(Error::TooManyRewardTiers);
+            }
+
+            if !options.reward_tiers.is_empty() && options.reward_collection.is_none() {
+                return Err(Error::IncorrectRewardTiers);
+            }
+
+            let mut min_amount: u128 = 0;
+            for tier in options.reward_tiers.iter() {
+                if tier.min_amount <= min_amount || tier.supply == 0 {
+                    return Err(Error::IncorrectRewardTiers);
+                }
+                if tier.uri.len() > MAX_DESCRIPTION_LENGTH {
+                    return Err(Error::DescriptionTooLong);
+                }
+                min_amount = tier.min_amount;
+            }
+
+            // Stretch goals build on top of the goal and each other.
+            if options.stretch_goals.len() > MAX_STRETCH_GOALS {
+                return Err(Error::IncorrectStretchGoals);
+            }
+
+            let mut amount = goal;
+            for stretch_goal in options.stretch_goals.iter() {
+                if stretch_goal.amount <= amount {
+                    return Err(Error::IncorrectStretchGoals);
+                }
+                if stretch_goal.description.len() > MAX_DESCRIPTION_LENGTH {
+                    return Err(Error::DescriptionTooLong);
+                }
+                amount = stretch_goal.amount;
+            }
+
+            // The cap can't stop the project from reaching its goal and stretch goals.
+            if let Some(max_budget) = options.max_budget {
+                if max_budget < amount {
+                    return Err(Error::IncorrectMaxBudget);
+                }
+            }
+
+            // Beneficiaries split the whole budget, each of them once.
+            if options.beneficiaries.len() > MAX_BENEFICIARIES {
+                return Err(Error::IncorrectBeneficiaries);
+            }
+
+            let mut shares: u32 = 0;
+            for (index, beneficiary) in options.beneficiaries.iter().enumerate() {
+                if beneficiary.share == 0
+                    || options.beneficiaries[..index]
+                        .iter()
+                        .any(|other| other.account == beneficiary.account)
+                {
+                    return Err(Error::IncorrectBeneficiaries);
+                }
+                shares += beneficiary.share as u32;
+            }
+
+            if !options.beneficiaries.is_empty() && shares != BASIS_POINTS as u32 {
+                return Err(Error::IncorrectBeneficiaries);
+            }
+
+            let co_authors = options
+                .beneficiaries
+                .iter()
+                .filter(|beneficiary| beneficiary.account != author)
+                .count()
+                + 1;
+            if options.approvals_required as usize > co_authors {
+                return Err(Error::IncorrectApprovals);
+            }
+
+            // The stream can't start before the campaign ends and replaces the milestones.
+            if let Some(vesting) = &options.vesting {
+                if vesting.duration == 0
+                    || vesting.cliff > vesting.duration
+                    || vesting.start < deadline
+                    || !options.milestones.is_empty()
+                {
+                    return Err(Error::IncorrectVesting);
+                }
+            }
+
+            if let Some(max_donation) = options.max_donation_per_account {
+                let min_donation = options.min_donation.unwrap_or(1);
+                if max_donation < min_donation {
+                    return Err(Error::IncorrectDonationLimits);
+                }
+            }
+
+            // Voting rules have to stay within the bounds set by the owner.
+            let bounds = &self.voting_bounds;
+            let voting_rules = match options.voting_rules {
+                Some(value) => value,
+                None => VotingRules {
+                    threshold: bounds.min_threshold,
+                    quorum: bounds.min_quorum,
+                    default_outcome: false,
+                },
+            };
+
+            if voting_rules.threshold < bounds.min_threshold
+                || voting_rules.threshold > bounds.max_threshold
+                || voting_rules.quorum < bounds.min_quorum
+                || voting_rules.quorum > bounds.max_quorum
+                || (voting_rules.default_outcome && !bounds.default_success_allowed)
+            {
+                return Err(Error::IncorrectVotingRules);
+            }
+
+            // Compose immutable project info.
+            let info = ProjectInfo {
+                description,
+                author,
+                create_time,
+                deadline,
+                goal,
+                voting_length: self.voting_length,
+                fee_percent: self.fee_percent,
+                token: options.token,
+                reward_tiers: options.reward_tiers,
+                reward_collection: options.reward_collection,
+                milestones: options.milestones,
+                withdrawal_lock_in: options.withdrawal_lock_in,
+                voting_rules,
+                quadratic_voting: options.quadratic_voting,
+                funding_mode: options.funding_mode,
+                stretch_goals: options.stretch_goals,
+                stretch_goal_votes: options.stretch_goal_votes,
+                max_budget: options.max_budget,
+                refund_excess: options.refund_excess,
+                min_donation: options.min_donation,
+                max_donation_per_account: options.max_donation_per_account,
+                private: options.private,
+                beneficiaries: options.beneficiaries,
+                approvals_required: options.approvals_required,
+                vesting: options.vesting,
+            };
+
+            // Initial voting state (no votes).
+            let voting_state = ProjectVotes {
+                ovr_voted_yes: 0,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            };
+
+            // Initialize the project in storage.
+            self.projects.insert(project_name.clone(), &info);
+            self.budgets.insert(project_name.clone(), &0);
+            self.voting_state
+                .insert(project_name.clone(), &voting_state);
+            self.claimed.insert(project_name.clone(), &false);
+            self.released.insert(project_name.clone(), &0);
+            self.milestone_index.insert(project_name.clone(), &0);
+            self.project_names
+                .insert(*self.project_count, &project_name);
+            *self.project_count += 1;
+
+            let authored = self.get_author_project_count(author)?;
+            self.author_projects
+                .insert((author, authored), &project_name);
+            self.author_project_count.insert(author, &(authored + 1));
+            self.author_project_position
+                .insert(project_name.clone(), &authored);
+
+            self.env().emit_event(ProjectCreated {
+                project_name,
+                author,
+                deadline,
+                goal,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_project_info(&self, project_name: String) -> Result<ProjectInfo, Error> {
+            // The deadline is extended by the author and by the pauses which happened before it.
+            match self.projects.get(project_name.clone()) {
+                Some(mut value) => {
+                    value.deadline += self
+                        .deadline_extension
+                        .get(project_name.clone())
+                        .unwrap_or_default();
+                    value.deadline = self.extended_deadline(&value);
+                    // Reaching the max budget ends the campaign right away.
+                    if let Some(closed_at) = self.closed.get(project_name) {
+                        value.deadline = closed_at;
+                    }
+                    Ok(value)
+                }
+                None => Err(Error::ProjectDoesntExist),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_collected_budget(&self, project_name: String) -> Result<u128, Error> {
+            match self.budgets.get(project_name) {
+                Some(value) => Result::Ok(value),
+                None => Err(Error::ProjectDoesntExist),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_voting_state(&self, project_name: String) -> Result<ProjectVotes, Error> {
+            match self.voting_state.get(project_name) {
+                Some(value) => Ok(value),
+                None => Err(Error::ProjectDoesntExist),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_author_claimed(&self, project_name: String) -> Result<bool, Error> {
+            match self.claimed.get(project_name) {
+                Some(value) => Result::Ok(value),
+                None => Err(Error::ProjectDoesntExist),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_project_cancelled(&self, project_name: String) -> Result<bool, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.cancelled.get(project_name).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_project_status(&self, project_name: String) -> Result<ProjectStatus, Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Ok(ProjectStatus::Cancelled);
+            }
+
+            if self.env().block_timestamp() < info.deadline {
+                return Ok(ProjectStatus::Active);
+            }
+
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            if !funding_reached(&info, budget) {
+                return Ok(ProjectStatus::Failed);
+            }
+
+            if self.get_author_claimed(project_name.clone())? {
+                return Ok(ProjectStatus::Claimed);
+            }
+
+            match self.current_voting_result(project_name, &info) {
+                Ok(true) => Ok(ProjectStatus::Succeeded),
+                Ok(false) => Ok(ProjectStatus::Failed),
+                Err(Error::CampaignResultUnknown) => Ok(ProjectStatus::Voting),
+                Err(error) => Err(error),
+            }
+        }
+
+        #[ink(message)]
+        pub fn cancel_project(&mut self, project_name: String) -> Result<(), Error> {
+            // Stops the campaign, every donor can refund right away.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let author = self.env().caller();
+            if !is_co_author(&info, author) {
+                return Err(Error::YouAreNotTheFather);
+            }
+
+            if self.env().block_timestamp() >= info.deadline {
+                return Err(Error::DeadlinePassed);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            if !self.approve_action(project_name.clone(), &info, AuthorAction::CancelProject)? {
+                return Ok(());
+            }
+
+            self.cancelled.insert(project_name.clone(), &true);
+
+            self.env().emit_event(ProjectCancelled {
+                project_name,
+                author,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_on_allowlist(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<bool, Error> {
+            // Everyone is allowed to donate to public projects.
+            let info = self.get_project_info(project_name.clone())?;
+
+            Ok(!info.private || self.allowlist.contains((project_name, account)))
+        }
+
+        #[ink(message)]
+        pub fn add_to_allowlist(
+            &mut self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            self.set_allowed(project_name, account, true)
+        }
+
+        #[ink(message)]
+        pub fn remove_from_allowlist(
+            &mut self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            // Donations already made stay where they are.
+            self.set_allowed(project_name, account, false)
+        }
+
+        fn set_allowed(
+            &mut self,
+            project_name: String,
+            account: AccountId,
+            allowed: bool,
+        ) -> Result<(), Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if !info.private {
+                return Err(Error::ProjectNotPrivate);
+            }
+
+            let action = AuthorAction::UpdateAllowlist(account, allowed);
+            if !self.approve_action(project_name.clone(), &info, action)? {
+                return Ok(());
+            }
+
+            match allowed {
+                true => self.allowlist.insert((project_name.clone(), account), &()),
+                false => self.allowlist.remove((project_name.clone(), account)),
+            }
+
+            self.env().emit_event(AllowlistChanged {
+                project_name,
+                account,
+                allowed,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_action_approvals(
+            &self,
+            project_name: String,
+            action: AuthorAction,
+        ) -> Result<Vec<AccountId>, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self
+                .approvals
+                .get((project_name, action))
+                .unwrap_or_default())
+        }
+
+        fn approve_action(
+            &mut self,
+            project_name: String,
+            info: &ProjectInfo,
+            action: AuthorAction,
+        ) -> Result<bool, Error> {
+            // Returns whether the action got enough approvals to happen now.
+            // The author has to be one of them, co-authors only add to the author's approval.
+            let account = self.env().caller();
+            if !is_co_author(info, account) {
+                return Err(Error::YouAreNotTheFather);
+            }
+
+            if info.approvals_required <= 1 {
+                return match account == info.author {
+                    true => Ok(true),
+                    false => Err(Error::YouAreNotTheFather),
+                };
+            }
+
+            let mut approvals = self.get_action_approvals(project_name.clone(), action)?;
+
+            if approvals.contains(&account) {
+                return Err(Error::AlreadyApproved);
+            }
+
+            approvals.push(account);
+            if approvals.len() >= info.approvals_required as usize
+                && approvals.contains(&info.author)
+            {
+                self.approvals.remove((project_name.clone(), action));
+                let mut actions = self
+                    .approved_actions
+                    .get(project_name.clone())
+                    .unwrap_or_default();
+                actions.retain(|approved| *approved != action);
+                self.approved_actions.insert(project_name, &actions);
+                return Ok(true);
+            }
+
+            if approvals.len() == 1 {
+                let mut actions = self
+                    .approved_actions
+                    .get(project_name.clone())
+                    .unwrap_or_default();
+                actions.push(action);
+                self.approved_actions.insert(project_name.clone(), &actions);
+            }
+            self.approvals
+                .insert((project_name.clone(), action), &approvals);
+
+            self.env().emit_event(ActionApproved {
+                project_name,
+                account,
+                action,
+                approvals: approvals.len() as u32,
+            });
+            Ok(false)
+        }
+
+        #[ink(message)]
+        pub fn get_donated_amount(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<u128, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Result::Ok(
+                self.donations
+                    .get((project_name, account))
+                    .unwrap_or_default(),
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_vote(&self, project_name: String, account: AccountId) -> Result<bool, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            match self.votes.get((project_name, account)) {
+                Some(value) => Ok(value),
+                None => Err(Error::NoSuchVote),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_vote_weight(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<u128, Error> {
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            if !self.votes.contains((project_name.clone(), account)) {
+                return Err(Error::NoSuchVote);
+            }
+
+            Ok(self.vote_weight(project_name, account, donated).0)
+        }
+
+        #[ink(message)]
+        pub fn get_quadratic_budget(&self, project_name: String) -> Result<u128, Error> {
+            // Sum of the quadratic weights of all donors, zero unless the project votes quadratically.
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.quadratic_budgets.get(project_name).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_delegate(
+            &self,
+            project_name: Option<String>,
+            account: AccountId,
+        ) -> Result<Option<AccountId>, Error> {
+            if let Some(project_name) = project_name.clone() {
+                if !self.projects.contains(project_name) {
+                    return Err(Error::ProjectDoesntExist);
+                }
+            }
+
+            Ok(self.delegates.get((project_name, account)))
+        }
+
+        #[ink(message)]
+        pub fn get_delegators(
+            &self,
+            project_name: Option<String>,
+            delegate: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<AccountId>, Error> {
+            if let Some(project_name) = project_name.clone() {
+                if !self.projects.contains(project_name) {
+                    return Err(Error::ProjectDoesntExist);
+                }
+            }
+
+            let count = self
+                .delegator_count
+                .get((project_name.clone(), delegate))
+                .unwrap_or_default();
+            let limit = core::cmp::min(limit, MAX_PAGE_SIZE);
+            let end = core::cmp::min(offset.saturating_add(limit), count);
+
+            let mut delegators = Vec::new();
+            for index in offset..end {
+                if let Some(value) = self.delegators.get((project_name.clone(), delegate, index)) {
+                    delegators.push(value)
+                }
+            }
+            Ok(delegators)
+        }
+
+        #[ink(message)]
+        pub fn delegate_vote(
+            &mut self,
+            project_name: Option<String>,
+            delegate: AccountId,
+        ) -> Result<(), Error> {
+            // The delegate votes with the donor's weight unless the donor votes on their own.
+            if let Some(project_name) = project_name.clone() {
+                if !self.projects.contains(project_name) {
+                    return Err(Error::ProjectDoesntExist);
+                }
+            }
+
+            let donor = self.env().caller();
+            if donor == delegate {
+                return Err(Error::IncorrectDelegate);
+            }
+
+            // Accounts with no weight to hand over can't take the delegate's slots.
+            let donated = match project_name.clone() {
+                Some(project_name) => self.get_donated_amount(project_name, donor)?,
+                None => self.get_donation_count(donor)? as u128,
+            };
+            if donated == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            // Delegating to the same account again frees the donor's slot first.
+            let previous = self.delegates.get((project_name.clone(), donor));
+            let count = self
+                .delegator_count
+                .get((project_name.clone(), delegate))
+                .unwrap_or_default();
+            if count >= MAX_DELEGATORS && previous != Some(delegate) {
+                return Err(Error::TooManyDelegators);
+            }
+
+            self.remove_delegation(project_name.clone(), donor);
+            let count = self
+                .delegator_count
+                .get((project_name.clone(), delegate))
+                .unwrap_or_default();
+
+            self.delegates
+                .insert((project_name.clone(), donor), &delegate);
+            self.delegators
+                .insert((project_name.clone(), delegate, count), &donor);
+            self.delegator_count
+                .insert((project_name.clone(), delegate), &(count + 1));
+            self.delegator_position
+                .insert((project_name.clone(), donor), &count);
+
+            self.env().emit_event(VoteDelegated {
+                project_name,
+                donor,
+                delegate: Some(delegate),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_delegation(&mut self, project_name: Option<String>) -> Result<(), Error> {
+            // Weight already used in the delegate's vote stays there, a direct vote moves it back.
+            let donor = self.env().caller();
+            match self.remove_delegation(project_name.clone(), donor) {
+                Some(_) => (),
+                None => return Err(Error::NoDelegation),
+            }
+
+            self.env().emit_event(VoteDelegated {
+                project_name,
+                donor,
+                delegate: None,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_donor_withdrawn(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<u128, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self
+                .withdrawn
+                .get((project_name, account))
+                .unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_donor_refunded(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<bool, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Result::Ok(
+                self.refunded
+                    .get((project_name, account))
+                    .unwrap_or_default(),
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_project_count(&self) -> Result<u32, Error> {
+            Ok(*self.project_count)
+        }
+
+        #[ink(message)]
+        pub fn get_projects(&self, offset: u32, limit: u32) -> Result<Vec<String>, Error> {
+            // Returns at most MAX_PAGE_SIZE names in the order of creation.
+            let limit = core::cmp::min(limit, MAX_PAGE_SIZE);
+            let end = core::cmp::min(offset.saturating_add(limit), *self.project_count);
+
+            let mut projects = Vec::new();
+            for index in offset..end {
+                if let Some(value) = self.project_names.get(index) {
+                    projects.push(value)
+                }
+            }
+            Ok(projects)
+        }
+
+        #[ink(message)]
+        pub fn get_author_project_count(&self, author: AccountId) -> Result<u32, Error> {
+            Ok(self.author_project_count.get(author).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_projects_by_author(
+            &self,
+            author: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<String>, Error> {
+            let count = self.get_author_project_count(author)?;
+            let limit = core::cmp::min(limit, MAX_PAGE_SIZE);
+            let end = core::cmp::min(offset.saturating_add(limit), count);
+
+            let mut projects = Vec::new();
+            for index in offset..end {
+                if let Some(value) = self.author_projects.get((author, index)) {
+                    projects.push(value)
+                }
+            }
+            Ok(projects)
+        }
+
+        #[ink(message)]
+        pub fn get_pending_author(&self, project_name: String) -> Result<Option<AccountId>, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.pending_authors.get(project_name))
+        }
+
+        #[ink(message)]
+        pub fn transfer_project(
+            &mut self,
+            project_name: String,
+            new_author: AccountId,
+        ) -> Result<(), Error> {
+            // The author role is handed over only when the new author accepts it.
+            let info = self.get_project_info(project_name.clone())?;
+
+            // Co-authors can only back a transfer the author proposed.
+            let action = AuthorAction::TransferProject(new_author);
+            if self.env().caller() != info.author
+                && !self
+                    .get_action_approvals(project_name.clone(), action)?
+                    .contains(&info.author)
+            {
+                return Err(Error::YouAreNotTheFather);
+            }
+
+            let author = info.author;
+            if !self.approve_action(project_name.clone(), &info, action)? {
+                return Ok(());
+            }
+
+            self.pending_authors
+                .insert(project_name.clone(), &new_author);
+
+            self.env().emit_event(ProjectTransferProposed {
+                project_name,
+                author,
+                pending_author: new_author,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn accept_project(&mut self, project_name: String) -> Result<(), Error> {
+            let mut info = match self.projects.get(project_name.clone()) {
+                Some(value) => value,
+                None => return Err(Error::ProjectDoesntExist),
+            };
+
+            let author = self.env().caller();
+            if self.pending_authors.get(project_name.clone()) != Some(author) {
+                return Err(Error::NotPendingAuthor);
+            }
+
+            // Authors can't donate to their own project, so donors have to withdraw or refund first.
+            let donated = self.get_donated_amount(project_name.clone(), author)?;
+            let refunded = self.get_donor_refunded(project_name.clone(), author)?;
+            if donated > 0 && !refunded {
+                return Err(Error::NewAuthorDonated);
+            }
+
+            // The previous author's share of the budget goes along with the role.
+            let previous_author = info.author;
+            let mut share = 0;
+            info.beneficiaries.retain(|beneficiary| {
+                if beneficiary.account == previous_author {
+                    share = beneficiary.share;
+                    return false;
+                }
+                true
+            });
+            if share > 0 {
+                match info
+                    .beneficiaries
+                    .iter_mut()
+                    .find(|beneficiary| beneficiary.account == author)
+                {
+                    Some(beneficiary) => beneficiary.share += share,
+                    None => info.beneficiaries.push(Beneficiary {
+                        account: author,
+                        share,
+                    }),
+                }
+            }
+            info.author = author;
+
+            // Approvals given under the previous co-authors don't carry over, and the
+            // threshold can't exceed the new co-author count.
+            let mut co_authors = vec![author];
+            for beneficiary in info.beneficiaries.iter() {
+                if !co_authors.contains(&beneficiary.account) {
+                    co_authors.push(beneficiary.account);
+                }
+            }
+            if info.approvals_required as usize > co_authors.len() {
+                info.approvals_required = co_authors.len() as u8;
+            }
+            let actions = self
+                .approved_actions
+                .get(project_name.clone())
+                .unwrap_or_default();
+            for action in actions {
+                self.approvals.remove((project_name.clone(), action));
+            }
+            self.approved_actions.remove(project_name.clone());
+
+            self.projects.insert(project_name.clone(), &info);
+            self.pending_authors.remove(project_name.clone());
+            self.move_authored_project(project_name.clone(), previous_author, author);
+
+            self.env().emit_event(ProjectTransferred {
+                project_name,
+                previous_author,
+                author,
+            });
+            Ok(())
+        }
+
+        fn move_authored_project(
+            &mut self,
+            project_name: String,
+            previous_author: AccountId,
+            author: AccountId,
+        ) {
+            let count = self
+                .author_project_count
+                .get(previous_author)
+                .unwrap_or_default();
+
+            // Swap with the last project of the previous author and shrink the list.
+            if let Some(position) = self.author_project_position.get(project_name.clone()) {
+                let last = count - 1;
+                if position != last {
+                    if let Some(moved) = self.author_projects.get((previous_author, last)) {
+                        self.author_projects
+                            .insert((previous_author, position), &moved);
+                        self.author_project_position.insert(moved, &position);
+                    }
+                }
+                self.author_projects.remove((previous_author, last));
+                self.author_project_count.insert(previous_author, &last);
+            }
+
+            let authored = self.author_project_count.get(author).unwrap_or_default();
+            self.author_projects
+                .insert((author, authored), &project_name);
+            self.author_project_count.insert(author, &(authored + 1));
+            self.author_project_position.insert(project_name, &authored);
+        }
+
+        #[ink(message)]
+        pub fn index_legacy_donation(&mut self, project_name: String) -> Result<(), Error> {
+            // Donations made before the upgrade are added to the donor's index on request.
+            let donor = self.env().caller();
+            let donated = self.get_donated_amount(project_name.clone(), donor)?;
+            if donated == 0
+                || !self.legacy_projects.contains(project_name.clone())
+                || self.legacy_indexed.contains((project_name.clone(), donor))
+            {
+                return Err(Error::NoLegacyDonation);
+            }
+
+            self.legacy_indexed
+                .insert((project_name.clone(), donor), &());
+            self.index_donation(project_name, donor);
+            Ok(())
+        }
+
+        fn index_donation(&mut self, project_name: String, donor: AccountId) {
+            let donated_to = self.donor_project_count.get(donor).unwrap_or_default();
+            self.donor_projects
+                .insert((donor, donated_to), &project_name);
+            self.donor_project_count.insert(donor, &(donated_to + 1));
+        }
+
+        #[ink(message)]
+        pub fn get_donation_count(&self, account: AccountId) -> Result<u32, Error> {
+            Ok(self.donor_project_count.get(account).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_donations_by_account(
+            &self,
+            account: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<DonationSummary>, Error> {
+            let count = self.get_donation_count(account)?;
+            let limit = core::cmp::min(limit, MAX_PAGE_SIZE);
+            let end = core::cmp::min(offset.saturating_add(limit), count);
+
+            let mut donations = Vec::new();
+            for index in offset..end {
+                let project_name = match self.donor_projects.get((account, index)) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let amount = self.get_donated_amount(project_name.clone(), account)?;
+                let refunded = self.get_donor_refunded(project_name.clone(), account)?;
+                // The project exists, so the only possible error is NoSuchVote.
+                let vote = self.get_vote(project_name.clone(), account).ok();
+
+                donations.push(DonationSummary {
+                    project_name,
+                    amount,
+                    refunded,
+                    vote,
+                });
+            }
+            Ok(donations)
+        }
+
+        #[ink(message)]
+        pub fn get_reward_tier(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<Option<u32>, Error> {
+            // The highest tier with free supply the account's donations qualify for.
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.reward_tier.get((project_name, account)))
+        }
+
+        #[ink(message)]
+        pub fn get_reward_tier_taken(&self, project_name: String, tier: u32) -> Result<u32, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self
+                .reward_tier_taken
+                .get((project_name, tier))
+                .unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_reward_minted(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<bool, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self
+                .reward_minted
+                .get((project_name, account))
+                .unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_projects_summary(
+            &self,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<ProjectSummary>, Error> {
+            // Everything a listing needs about a page of projects in one call.
+            let names = self.get_projects(offset, limit)?;
+
+            let mut summaries = Vec::new();
+            for project_name in names {
+                let info = self.get_project_info(project_name.clone())?;
+                let budget = self.get_collected_budget(project_name.clone())?;
+                let votes = self.get_voting_state(project_name.clone())?;
+
+                summaries.push(ProjectSummary {
+                    project_name,
+                    info,
+                    budget,
+                    votes,
+                });
+            }
+            Ok(summaries)
+        }
+
+        #[ink(message, payable)]
+        pub fn make_donation(&mut self, project_name: String) -> Result<(), Error> {
+            let value = self.env().transferred_value();
+            self.donate(project_name, value, false)
+        }
+
+        #[ink(message)]
+        pub fn make_token_donation(
+            &mut self,
+            project_name: String,
+            amount: u128,
+        ) -> Result<(), Error> {
+            // The donor has to approve the contract to spend `amount` of the project's token first.
+            self.donate(project_name, amount, true)
+        }
+
+        fn donate(
+            &mut self,
+            project_name: String,
+            value: u128,
+            in_tokens: bool,
+        ) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            // Fetch project info. It checks if the project exists.
+            let info = self.get_project_info(project_name.clone())?;
+
+            // Empty donations would only write storage.
+            if value == 0 {
+                return Err(Error::DonationTooSmall);
+            }
+
+            // Donations after the deadline are not permitted.
+            let current_time = self.env().block_timestamp();
+            if current_time >= info.deadline {
+                return Err(Error::DeadlinePassed);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            // Assuming the author can't donate to their own project.
+            let donor = self.env().caller();
+            if is_co_author(&info, donor) {
+                return Err(Error::CantDonateOwnProject);
+            }
+
+            // Private projects are funded by the members of the allowlist only.
+            if info.private && !self.allowlist.contains((project_name.clone(), donor)) {
+                return Err(Error::NotOnAllowlist);
+            }
+
+            // The donation has to be made in the project's currency.
+            if info.token.is_some() != in_tokens {
+                return Err(Error::WrongCurrency);
+            }
+
+            // Fetch the already donated amount.
+            let donated = self.get_donated_amount(project_name.clone(), donor)?;
+
+            // Fetch the project's collected budget.
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            // Donations over the max budget are either cut down or rejected.
+            let mut value = value;
+            let mut excess = 0;
+            if let Some(max_budget) = info.max_budget {
+                if budget + value > max_budget {
+                    if !info.refund_excess {
+                        return Err(Error::MaxBudgetExceeded);
+                    }
+                    excess = budget + value - max_budget;
+                    value = max_budget - budget;
+                }
+            }
+
+            // The limits apply to the account's running total.
+            if let Some(min_donation) = info.min_donation {
+                if donated + value < min_donation {
+                    return Err(Error::DonationTooSmall);
+                }
+            }
+
+            if let Some(max_donation) = info.max_donation_per_account {
+                if donated + value > max_donation {
+                    return Err(Error::DonationTooLarge);
+                }
+            }
+
+            // Tokens are pulled from the donor, the native currency came with the call.
+            match info.token {
+                Some(token) => self.transfer_tokens_from(token, donor, value)?,
+                None => {
+                    if excess > 0 && self.env().transfer(donor, excess).is_err() {
+                        return Err(Error::TransferFailed);
+                    }
+                }
+            }
+
+            // Make note of the donation and update the collected budget.
+            self.donations
+                .insert((project_name.clone(), donor), &(donated + value));
+            self.budgets.insert(project_name.clone(), &(budget + value));
+
+            self.update_reward_tier(project_name.clone(), &info, donor, donated + value);
+            self.update_quadratic_budget(project_name.clone(), &info, donated, donated + value);
+            self.update_matching(project_name.clone(), donor, value, true);
+
+            let withdrawn = self.get_donor_withdrawn(project_name.clone(), donor)?;
+
+            // The first donation adds the project to the donor's index.
+            if donated == 0 && withdrawn == 0 {
+                self.index_donation(project_name.clone(), donor);
+            }
+
+            self.env().emit_event(DonationMade {
+                project_name: project_name.clone(),
+                donor,
+                amount: value,
+            });
+
+            if info.max_budget == Some(budget + value) {
+                self.closed.insert(project_name.clone(), &current_time);
+                self.env().emit_event(MaxBudgetReached {
+                    project_name,
+                    budget: budget + value,
+                    closed_at: current_time,
+                });
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn withdraw_donation(
+            &mut self,
+            project_name: String,
+            amount: u128,
+        ) -> Result<(), Error> {
+            // Fetch project info. It checks if the project exists.
+            let info = self.get_project_info(project_name.clone())?;
+
+            // The author decides at creation whether donors may change their minds.
+            let lock_in = match info.withdrawal_lock_in {
+                Some(value) => value,
+                None => return Err(Error::WithdrawalsNotAllowed),
+            };
+
+            let current_time = self.env().block_timestamp();
+            if current_time >= info.deadline {
+                return Err(Error::DeadlinePassed);
+            }
+
+            if current_time >= info.deadline.saturating_sub(lock_in) {
+                return Err(Error::WithdrawalsLocked);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            let donor = self.env().caller();
+            let donated = self.get_donated_amount(project_name.clone(), donor)?;
+
+            if donated == 0 {
+                return Err(Error::NoFundsToRefund);
+            }
+
+            if amount == 0 || amount > donated {
+                return Err(Error::IncorrectWithdrawalAmount);
+            }
+
+            // Whatever stays donated still has to meet the minimum.
+            if let Some(min_donation) = info.min_donation {
+                if amount < donated && donated - amount < min_donation {
+                    return Err(Error::IncorrectWithdrawalAmount);
+                }
+            }
+
+            self.return_donation(project_name.clone(), &info, donor, donated, amount)?;
+
+            self.env().emit_event(DonationWithdrawn {
+                project_name,
+                donor,
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn make_vote(&mut self, project_name: String, vote: bool) -> Result<(), Error> {
+            // Fetch project info. It checks if the project exists.
+            let info = self.get_project_info(project_name.clone())?;
+
+            // Voting before the project's deadline is not permitted.
+            let current_time = self.env().block_timestamp();
+            if current_time < info.deadline {
+                return Err(Error::DeadlineNotPassedYet);
+            }
+
+            // Voting after the voting deadline is not permitted
+            if current_time > info.deadline + info.voting_length {
+                return Err(Error::VotingDeadlinePassed);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            if matches!(info.funding_mode, FundingMode::Flexible) {
+                return Err(Error::VotingNotRequired);
+            }
+
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            // Voting is needed only if the goal of the project was reached.
+            if !funding_reached(&info, budget) {
+                return Err(Error::GoalNotReached);
+            }
+
+            let account = self.env().caller();
+
+            // Project exists so the only possible error is NoSuchVote, which means no vote yet.
+            let previous = self.get_vote(project_name.clone(), account).ok();
+
+            // Duplicate votes are not permitted.
+            if previous == Some(vote) {
+                return Err(Error::AlreadyVoted);
+            }
+
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            let mut voting_state = self.get_voting_state(project_name.clone())?;
+
+            // Votes can change until the result was acted upon, even if it's known early.
+            let locked = self.votes_locked(project_name.clone());
+
+            let (weight, quadratic_weight) = match previous {
+                // A changed vote moves its weight to the other side.
+                Some(previous) => {
+                    if locked {
+                        return Err(Error::VotingAlreadyDecided);
+                    }
+
+                    let (weight, quadratic_weight) =
+                        self.vote_weight(project_name.clone(), account, donated);
+                    match previous {
+                        true => {
+                            voting_state.ovr_voted_yes -= weight;
+                            voting_state.quad_voted_yes -= quadratic_weight;
+                        }
+                        false => {
+                            voting_state.ovr_voted_no -= weight;
+                            voting_state.quad_voted_no -= quadratic_weight;
+                        }
+                    }
+                    (weight, quadratic_weight)
+                }
+                // The first vote counts the donation and the weight delegated to the account.
+                None => {
+                    // Voting directly takes the donation back from the delegate's vote.
+                    if let Some(delegate) =
+                        self.delegated_votes.get((project_name.clone(), account))
+                    {
+                        if locked && self.votes.contains((project_name.clone(), delegate)) {
+                            return Err(Error::VotingAlreadyDecided);
+                        }
+                        self.take_delegated_weight(
+                            project_name.clone(),
+                            delegate,
+                            (donated, quadratic_weight(&info, donated)),
+                            &mut voting_state,
+                        );
+                        self.delegated_votes.remove((project_name.clone(), account));
+                    }
+
+                    let (delegated, quadratic_delegated) =
+                        self.collect_delegated_weight(project_name.clone(), &info, account);
+                    (
+                        donated + delegated,
+                        quadratic_weight(&info, donated) + quadratic_delegated,
+                    )
+                }
+            };
+
+            // No donation, no vote.
+            if weight == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            // Update the corresponding weighted sum of votes according to the vote.
+            match vote {
+                true => {
+                    voting_state.ovr_voted_yes += weight;
+                    voting_state.quad_voted_yes += quadratic_weight;
+                }
+                false => {
+                    voting_state.ovr_voted_no += weight;
+                    voting_state.quad_voted_no += quadratic_weight;
+                }
+            }
+
+            // Override the voting state in storage & make note of the vote.
+            self.voting_state
+                .insert(project_name.clone(), &voting_state);
+            self.votes.insert((project_name.clone(), account), &vote);
+            self.vote_weights
+                .insert((project_name.clone(), account), &(weight, quadratic_weight));
+
+            match previous {
+                Some(_) => self.env().emit_event(VoteChanged {
+                    project_name,
+                    donor: account,
+                    vote: Some(vote),
+                    weight,
+                }),
+                None => self.env().emit_event(VoteCast {
+                    project_name,
+                    donor: account,
+                    vote,
+                    weight,
+                }),
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn withdraw_vote(&mut self, project_name: String) -> Result<(), Error> {
+            // Turns the vote into an abstention until the result is acted upon.
+            let info = self.get_project_info(project_name.clone())?;
+
+            if self.env().block_timestamp() > info.deadline + info.voting_length {
+                return Err(Error::VotingDeadlinePassed);
+            }
+
+            let account = self.env().caller();
+
+            let vote = self.get_vote(project_name.clone(), account)?;
+
+            if self.votes_locked(project_name.clone()) {
+                return Err(Error::VotingAlreadyDecided);
+            }
+
+            let mut voting_state = self.get_voting_state(project_name.clone())?;
+
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            let (weight, quadratic_weight) =
+                self.vote_weight(project_name.clone(), account, donated);
+            match vote {
+                true => {
+                    voting_state.ovr_voted_yes -= weight;
+                    voting_state.quad_voted_yes -= quadratic_weight;
+                }
+                false => {
+                    voting_state.ovr_voted_no -= weight;
+                    voting_state.quad_voted_no -= quadratic_weight;
+                }
+            }
+
+            // Delegated weight is collected again if the account votes once more.
+            self.voting_state
+                .insert(project_name.clone(), &voting_state);
+            self.votes.remove((project_name.clone(), account));
+            self.vote_weights.remove((project_name.clone(), account));
+
+            self.env().emit_event(VoteChanged {
+                project_name,
+                donor: account,
+                vote: None,
+                weight,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_project_voting_result(&self, project_name: String) -> Result<bool, Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            let voting_state = self.get_voting_state(project_name.clone())?;
+
+            self.voting_result(
+                project_name,
+                &voting_state,
+                &info,
+                info.deadline + info.voting_length,
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_milestone_progress(&self, project_name: String) -> Result<(u32, u128), Error> {
+            // Returns the index of the next milestone to be released and the budget released so far.
+            let index = match self.milestone_index.get(project_name.clone()) {
+                Some(value) => value,
+                None => return Err(Error::ProjectDoesntExist),
+            };
+
+            match self.released.get(project_name) {
+                Some(value) => Ok((index, value)),
+                None => Err(Error::ProjectDoesntExist),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_milestone_voting_state(
+            &self,
+            project_name: String,
+            milestone: u32,
+        ) -> Result<ProjectVotes, Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if milestone as usize >= info.milestones.len() {
+                return Err(Error::NoSuchMilestone);
+            }
+
+            // The first milestone is decided by the campaign voting.
+            if milestone == 0 {
+                return self.get_voting_state(project_name);
+            }
+
+            match self.milestone_voting_state.get((project_name, milestone)) {
+                Some(value) => Ok(value),
+                None => Err(Error::MilestoneVoteNotStarted),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_milestone_vote(
+            &self,
+            project_name: String,
+            milestone: u32,
+            account: AccountId,
+        ) -> Result<bool, Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if milestone as usize >= info.milestones.len() {
+                return Err(Error::NoSuchMilestone);
+            }
+
+            if milestone == 0 {
+                return self.get_vote(project_name, account);
+            }
+
+            match self.milestone_votes.get((project_name, milestone, account)) {
+                Some(value) => Ok(value),
+                None => Err(Error::NoSuchVote),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_milestone_voting_result(
+            &self,
+            project_name: String,
+            milestone: u32,
+        ) -> Result<bool, Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if milestone as usize >= info.milestones.len() {
+                return Err(Error::NoSuchMilestone);
+            }
+
+            if milestone == 0 {
+                return self.get_project_voting_result(project_name);
+            }
+
+            let start = match self
+                .milestone_voting_start
+                .get((project_name.clone(), milestone))
+            {
+                Some(value) => value,
+                None => return Err(Error::CampaignResultUnknown),
+            };
+
+            let voting_state = self.get_milestone_voting_state(project_name.clone(), milestone)?;
+
+            self.voting_result(
+                project_name,
+                &voting_state,
+                &info,
+                start + info.voting_length,
+            )
+        }
+
+        #[ink(message)]
+        pub fn start_milestone_vote(&mut self, project_name: String) -> Result<(), Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if info.milestones.is_empty() {
+                return Err(Error::NoMilestones);
+            }
+
+            if matches!(info.funding_mode, FundingMode::Flexible) {
+                return Err(Error::VotingNotRequired);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            // Both the author and the donors can open the voting, so that the author can't stall refunds.
+            let account = self.env().caller();
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            if !is_co_author(&info, account) && donated == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            let (milestone, _) = self.get_milestone_progress(project_name.clone())?;
+
+            if milestone as usize >= info.milestones.len() {
+                return Err(Error::AllMilestonesReleased);
+            }
+
+            // The first milestone is decided by the campaign voting which starts at the deadline.
+            if milestone == 0
+                || self
+                    .milestone_voting_start
+                    .contains((project_name.clone(), milestone))
+            {
+                return Err(Error::MilestoneVoteAlreadyStarted);
+            }
+
+            let voting_state = ProjectVotes {
+                ovr_voted_yes: 0,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            };
+
+            self.milestone_voting_start.insert(
+                (project_name.clone(), milestone),
+                &self.env().block_timestamp(),
+            );
+            self.milestone_voting_state
+                .insert((project_name.clone(), milestone), &voting_state);
+
+            self.env().emit_event(MilestoneVoteStarted {
+                project_name,
+                milestone,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn make_milestone_vote(
+            &mut self,
+            project_name: String,
+            vote: bool,
+        ) -> Result<(), Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if info.milestones.is_empty() {
+                return Err(Error::NoMilestones);
+            }
+
+            if matches!(info.funding_mode, FundingMode::Flexible) {
+                return Err(Error::VotingNotRequired);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            let (milestone, _) = self.get_milestone_progress(project_name.clone())?;
+
+            if milestone as usize >= info.milestones.len() {
+                return Err(Error::AllMilestonesReleased);
+            }
+
+            // Votes on the first milestone are cast with `make_vote`.
+            let start = match self
+                .milestone_voting_start
+                .get((project_name.clone(), milestone))
+            {
+                Some(value) => value,
+                None => return Err(Error::MilestoneVoteNotStarted),
+            };
+
+            // Voting after the voting deadline is not permitted
+            if self.env().block_timestamp() > start + info.voting_length {
+                return Err(Error::VotingDeadlinePassed);
+            }
+
+            let account = self.env().caller();
+
+            if self
+                .get_milestone_vote(project_name.clone(), milestone, account)
+                .is_ok()
+            {
+                return Err(Error::AlreadyVoted);
+            }
+
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            // No donation, no vote.
+            if donated == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            let mut voting_state =
+                self.get_milestone_voting_state(project_name.clone(), milestone)?;
+
+            match vote {
+                true => {
+                    voting_state.ovr_voted_yes += donated;
+                    voting_state.quad_voted_yes += quadratic_weight(&info, donated);
+                }
+                false => {
+                    voting_state.ovr_voted_no += donated;
+                    voting_state.quad_voted_no += quadratic_weight(&info, donated);
+                }
+            }
+
+            self.milestone_voting_state
+                .insert((project_name.clone(), milestone), &voting_state);
+            self.milestone_votes
+                .insert((project_name.clone(), milestone, account), &vote);
+
+            self.env().emit_event(MilestoneVoteCast {
+                project_name,
+                donor: account,
+                milestone,
+                vote,
+                weight: donated,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_extension_proposal(&self, project_name: String) -> Result<u64, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            match self.extension_proposal.get(project_name) {
+                Some(value) => Ok(value),
+                None => Err(Error::NoSuchVote),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_extension_voting_state(
+            &self,
+            project_name: String,
+        ) -> Result<ProjectVotes, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            match self.extension_voting_state.get(project_name) {
+                Some(value) => Ok(value),
+                None => Err(Error::NoSuchVote),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_extension_vote(
+            &self,
+            project_name: String,
+            account: AccountId,
+        ) -> Result<bool, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            match self.extension_votes.get((project_name, account)) {
+                Some((vote, _)) => Ok(vote),
+                None => Err(Error::NoSuchVote),
+            }
+        }
+
+        #[ink(message)]
+        pub fn extend_deadline(
+            &mut self,
+            project_name: String,
+            extension: u64,
+        ) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            let info = self.get_project_info(project_name.clone())?;
+
+            if !is_co_author(&info, self.env().caller()) {
+                return Err(Error::YouAreNotTheFather);
+            }
+
+            if self.env().block_timestamp() >= info.deadline {
+                return Err(Error::DeadlinePassed);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            // The deadline can be extended only once.
+            if self.deadline_extension.contains(project_name.clone())
+                || self.extension_proposal.contains(project_name.clone())
+            {
+                return Err(Error::DeadlineAlreadyExtended);
+            }
+
+            if extension == 0 || extension > MAX_DEADLINE_EXTENSION {
+                return Err(Error::IncorrectExtension);
+            }
+
+            let action = AuthorAction::ExtendDeadline(extension);
+            if !self.approve_action(project_name.clone(), &info, action)? {
+                return Ok(());
+            }
+
+            // Short extensions are applied right away, longer ones need the donors' approval.
+            if extension <= EXTENSION_GRACE_PERIOD {
+                self.deadline_extension
+                    .insert(project_name.clone(), &extension);
+                self.env().emit_event(DeadlineExtended {
+                    project_name,
+                    deadline: info.deadline + extension,
+                });
+                return Ok(());
+            }
+
+            let voting_state = ProjectVotes {
+                ovr_voted_yes: 0,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            };
+            self.extension_proposal
+                .insert(project_name.clone(), &extension);
+            self.extension_voting_state
+                .insert(project_name.clone(), &voting_state);
+
+            self.env().emit_event(DeadlineExtensionProposed {
+                project_name,
+                extension,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn make_extension_vote(
+            &mut self,
+            project_name: String,
+            vote: bool,
+        ) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            let info = self.get_project_info(project_name.clone())?;
+
+            // Donors decide until the current deadline, objecting lets them refund right away.
+            if self.env().block_timestamp() >= info.deadline {
+                return Err(Error::DeadlinePassed);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            let mut voting_state = self.get_extension_voting_state(project_name.clone())?;
+
+            let account = self.env().caller();
+
+            if self
+                .get_extension_vote(project_name.clone(), account)
+                .is_ok()
+            {
+                return Err(Error::AlreadyVoted);
+            }
+
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            // No donation, no vote.
+            if donated == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            match vote {
+                true => voting_state.ovr_voted_yes += donated,
+                false => voting_state.ovr_voted_no += donated,
+            }
+
+            self.extension_voting_state
+                .insert(project_name.clone(), &voting_state);
+            self.extension_votes
+                .insert((project_name.clone(), account), &(vote, donated));
+
+            self.env().emit_event(ExtensionVoteCast {
+                project_name: project_name.clone(),
+                donor: account,
+                vote,
+                weight: donated,
+            });
+
+            self.approve_extension(project_name);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_unlocked_stretch_goals(&self, project_name: String) -> Result<u32, Error> {
+            // Stretch goals are ordered, so the first ones up to the returned count are unlocked.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let budget = self.get_collected_budget(project_name)?;
+
+            let mut unlocked = 0;
+            for stretch_goal in info.stretch_goals.iter() {
+                if budget < stretch_goal.amount {
+                    break;
+                }
+                unlocked += 1;
+            }
+            Ok(unlocked)
+        }
+
+        #[ink(message)]
+        pub fn get_stretch_voting_state(
+            &self,
+            project_name: String,
+            stretch_goal: u32,
+        ) -> Result<ProjectVotes, Error> {
+            let unlocked = self.get_unlocked_stretch_goals(project_name.clone())?;
+
+            if stretch_goal >= unlocked {
+                return Err(Error::NoSuchStretchGoal);
+            }
+
+            Ok(
+                match self.stretch_voting_state.get((project_name, stretch_goal)) {
+                    Some(value) => value,
+                    None => ProjectVotes {
+                        ovr_voted_yes: 0,
+                        ovr_voted_no: 0,
+                        quad_voted_yes: 0,
+                        quad_voted_no: 0,
+                    },
+                },
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_stretch_voting_result(
+            &self,
+            project_name: String,
+            stretch_goal: u32,
+        ) -> Result<bool, Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            let voting_state = self.get_stretch_voting_state(project_name.clone(), stretch_goal)?;
+
+            self.voting_result(
+                project_name,
+                &voting_state,
+                &info,
+                info.deadline + info.voting_length,
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_claimable_budget(&self, project_name: String) -> Result<u128, Error> {
+            // The budget up to the first rejected stretch goal, the rest can be refunded.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            if !info.stretch_goal_votes {
+                return Ok(budget);
+            }
+
+            let unlocked = self.get_unlocked_stretch_goals(project_name.clone())?;
+
+            for stretch_goal in 0..unlocked {
+                if !self.get_stretch_voting_result(project_name.clone(), stretch_goal)? {
+                    return Ok(info.stretch_goals[stretch_goal as usize].amount);
+                }
+            }
+            Ok(budget)
+        }
+
+        #[ink(message)]
+        pub fn make_stretch_vote(
+            &mut self,
+            project_name: String,
+            stretch_goal: u32,
+            vote: bool,
+        ) -> Result<(), Error> {
+            let info = self.get_project_info(project_name.clone())?;
+
+            if !info.stretch_goal_votes {
+                return Err(Error::VotingNotRequired);
+            }
+
+            // Stretch goals are voted on together with the project.
+            let current_time = self.env().block_timestamp();
+            if current_time < info.deadline {
+                return Err(Error::DeadlineNotPassedYet);
+            }
+
+            if current_time > info.deadline + info.voting_length {
+                return Err(Error::VotingDeadlinePassed);
+            }
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            let mut voting_state =
+                self.get_stretch_voting_state(project_name.clone(), stretch_goal)?;
+
+            let account = self.env().caller();
+
+            if self
+                .stretch_votes
+                .contains((project_name.clone(), stretch_goal, account))
+            {
+                return Err(Error::AlreadyVoted);
+            }
+
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            // No donation, no vote.
+            if donated == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            match vote {
+                true => {
+                    voting_state.ovr_voted_yes += donated;
+                    voting_state.quad_voted_yes += quadratic_weight(&info, donated);
+                }
+                false => {
+                    voting_state.ovr_voted_no += donated;
+                    voting_state.quad_voted_no += quadratic_weight(&info, donated);
+                }
+            }
+
+            self.stretch_voting_state
+                .insert((project_name.clone(), stretch_goal), &voting_state);
+            self.stretch_votes
+                .insert((project_name.clone(), stretch_goal, account), &vote);
+
+            self.env().emit_event(StretchVoteCast {
+                project_name,
+                donor: account,
+                stretch_goal,
+                vote,
+                weight: donated,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn refund_stretch_portion(&mut self, project_name: String) -> Result<(), Error> {
+            // Refunds the donor's share of the budget above the first rejected stretch goal.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let donor = self.env().caller();
+
+            let refunded = self
+                .stretch_refunded
+                .get((project_name.clone(), donor))
+                .unwrap_or_default();
+            if self.get_donor_refunded(project_name.clone(), donor)? || refunded {
+                return Err(Error::NoFundsToRefund);
+            }
+
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            let claimable = self.get_claimable_budget(project_name.clone())?;
+
+            let donated = self.get_donated_amount(project_name.clone(), donor)?;
+
+            let refund = match budget {
+                0 => 0,
+                _ => mul_div(donated, budget - claimable, budget),
+            };
+
+            if refund == 0 {
+                return Err(Error::NoFundsToRefund);
+            }
+
+            self.stretch_refunded
+                .insert((project_name.clone(), donor), &true);
+
+            match self.transfer_funds(info.token, donor, refund) {
+                Ok(_) => (),
+                Err(_) => return Err(Error::TransferFailed),
+            }
+
+            self.env().emit_event(RefundIssued {
+                project_name,
+                donor,
+                amount: refund,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn refund_donation(&mut self, project_name: String) -> Result<(), Error> {
+            // Fetch project info. It checks if the project exists.
+            let info = self.get_project_info(project_name.clone())?;
+
+            // Cancelled projects refund everyone right away.
+            let cancelled = self.get_project_cancelled(project_name.clone())?;
+
+            let donor = self.env().caller();
+
+            // Making a refund is only possible after the deadline has passed.
+            let current_time = self.env().block_timestamp();
+            if !cancelled && current_time < info.deadline {
+                // Donors objecting to the deadline extension may leave early.
+                match self.get_extension_vote(project_name.clone(), donor) {
+                    Ok(false) => (),
+                    _ => return Err(Error::DeadlineNotPassedYet),
+                }
+
+                let donated = self.get_donated_amount(project_name.clone(), donor)?;
+
+                if donated == 0 {
+                    return Err(Error::NoFundsToRefund);
+                }
+
+                self.return_donation(project_name.clone(), &info, donor, donated, donated)?;
+
+                self.env().emit_event(RefundIssued {
+                    project_name,
+                    donor,
+                    amount: donated,
+                });
+                return Ok(());
+            }
+
+            // Verify if already refunded it.
+            if self.get_donor_refunded(project_name.clone(), donor)? {
+                return Err(Error::NoFundsToRefund);
+            }
+
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            // If the goal was reached then refunds are possible only if the voting indicates it.
+            if !cancelled
+                && funding_reached(&info, budget)
+                && self.current_voting_result(project_name.clone(), &info)?
+                && !self.vesting_cancelled.contains(project_name.clone())
+            {
+                return Err(Error::CampaignSuccessfulNoRefunds);
+            }
+
+            let donated = self.get_donated_amount(project_name.clone(), donor)?;
+
+            // No donation, no refund
+            if donated == 0 {
+                return Err(Error::NoFundsToRefund);
+            }
+
+            // Only the part of the budget which wasn't released yet is refunded, proportionally.
+            let (_, released) = self.get_milestone_progress(project_name.clone())?;
+            let mut refund = mul_div(donated, budget - released, budget);
+
+            // A stopped stream leaves its unvested remainder to the donors, except for the match.
+            if self.vesting_cancelled.contains(project_name.clone()) {
+                let (unvested, _) = self.split_unvested(project_name.clone())?;
+                refund += mul_div(donated, unvested, budget);
+            }
+
+            // The portion above a rejected stretch goal might have been refunded already.
+            let stretch_refunded = self
+                .stretch_refunded
+                .get((project_name.clone(), donor))
+                .unwrap_or_default();
+            if stretch_refunded {
+                let claimable = self.get_claimable_budget(project_name.clone())?;
+                refund -= mul_div(donated, budget - claimable, budget);
+            }
+
+            // All conditions to make a refund are met.
+
+            // Make note of the refund
+            self.refunded.insert((project_name.clone(), donor), &true);
+            self.refund_issued.insert(project_name.clone(), &());
+
+            // Transfer the refund.
+            match self.transfer_funds(info.token, donor, refund) {
+                Ok(_) => (),
+                Err(_) => return Err(Error::TransferFailed),
+            }
+
+            self.env().emit_event(RefundIssued {
+                project_name,
+                donor,
+                amount: refund,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn claim_budget(&mut self, project_name: String) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            // Fetch project info. It checks if the project exists.
+            let info = self.get_project_info(project_name.clone())?;
+
+            if self.get_project_cancelled(project_name.clone())? {
+                return Err(Error::ProjectCancelled);
+            }
+
+            // Claiming the collected budget is only possible after the deadline.
+            let current_time = self.env().block_timestamp();
+            if current_time < info.deadline {
+                return Err(Error::DeadlineNotPassedYet);
+            }
+
+            // Any of the co-authors can claim the budget.
+            if !is_co_author(&info, self.env().caller()) {
+                return Err(Error::YouAreNotTheFather);
+            }
+
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            // The campaign can be successful only if the goal was reached.
+            if !funding_reached(&info, budget) {
+                return Err(Error::GoalNotReached);
+            }
+
+            // Verify if already claimed.
+            if self.get_author_claimed(project_name.clone())? {
+                return Err(Error::NoFundsToClaim);
+            }
+
+            // The budget can be claimed by the author only if the voting indicates it.
+            if !self.current_voting_result(project_name.clone(), &info)? {
+                return Err(Error::CampaignUnsuccessfulNoClaims);
+            }
+
+            // No budget, no claim.
+            if budget == 0 {
+                return Err(Error::NoFundsToClaim);
+            }
+
+            // A rejected stretch goal caps the budget, the rest goes back to the donors.
+            let budget = self.get_claimable_budget(project_name.clone())?;
+
+            let (milestone, released) = self.get_milestone_progress(project_name.clone())?;
+
+            // Without milestones the whole budget is a single tranche.
+            // The last tranche takes whatever is left so that no rounding dust remains.
+            let is_last = milestone as usize + 1 >= info.milestones.len();
+            let tranche = match is_last {
+                true => budget - released,
+                false => mul_div(
+                    budget,
+                    info.milestones[milestone as usize].share as u128,
+                    100,
+                ),
+            };
+
+            // The matched amount comes with the last tranche, once the round was finalised.
+            let matched = match is_last {
+                true => self.get_matched_amount(project_name.clone())?,
+                false => 0,
+            };
+
+            // All conditions to claim were met.
+
+            // Make note of the claim.
+            self.released
+                .insert(project_name.clone(), &(released + tranche));
+            self.milestone_index
+                .insert(project_name.clone(), &(milestone + 1));
+            if is_last {
+                self.claimed.insert(project_name.clone(), &true);
+            }
+
+            let tranche = tranche + matched;
+
+            // Vested budgets are paid out bit by bit through `release_vested`.
+            // The deadline may have moved past the scheduled start, the stream starts with the claim then.
+            if let Some(vesting) = &info.vesting {
+                let start = core::cmp::max(vesting.start, self.env().block_timestamp());
+                self.vesting_total.insert(project_name.clone(), &tranche);
+                self.vesting_started.insert(project_name.clone(), &start);
+                self.vesting_matched.insert(project_name.clone(), &matched);
+                self.env().emit_event(VestingStarted {
+                    project_name,
+                    amount: tranche,
+                });
+                return Ok(());
+            }
+
+            self.pay_out(project_name, &info, tranche)
+        }
+
+        fn pay_out(
+            &mut self,
+            project_name: String,
+            info: &ProjectInfo,
+            amount: u128,
+        ) -> Result<(), Error> {
+            // calculate and transfer fee
+            let fee = mul_div(amount, info.fee_percent as u128, 100);
+
+            match self.transfer_funds(info.token, self.owner_account, fee) {
+                Ok(_) => (),
+                Err(_) => return Err(Error::TransferFailed),
+            }
+
+            self.env().emit_event(FeePaid {
+                project_name: project_name.clone(),
+                owner: self.owner_account,
+                amount: fee,
+            });
+
+            // Transfer the claim, split across the beneficiaries.
+            for (author, amount) in split_payout(info, amount - fee) {
+                match self.transfer_funds(info.token, author, amount) {
+                    Ok(_) => (),
+                    Err(_) => return Err(Error::TransferFailed),
+                }
+
+                self.env().emit_event(BudgetClaimed {
+                    project_name: project_name.clone(),
+                    author,
+                    amount,
+                });
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_vested_amount(&self, project_name: String) -> Result<u128, Error> {
+            // Vesting stops at the time the donors cancelled the stream.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let vesting = match info.vesting {
+                Some(value) => value,
+                None => return Err(Error::NoVesting),
+            };
+
+            let total = match self.vesting_total.get(project_name.clone()) {
+                Some(value) => value,
+                None => return Ok(0),
+            };
+            let start = match self.vesting_started.get(project_name.clone()) {
+                Some(value) => value,
+                None => vesting.start,
+            };
+
+            let now = match self.vesting_cancelled.get(project_name) {
+                Some(value) => core::cmp::min(value, self.env().block_timestamp()),
+                None => self.env().block_timestamp(),
+            };
+
+            if now < start + vesting.cliff {
+                return Ok(0);
+            }
+
+            let elapsed = core::cmp::min(now - start, vesting.duration);
+            Ok(mul_div(total, elapsed as u128, vesting.duration as u128))
+        }
+
+        #[ink(message)]
+        pub fn get_vesting_released(&self, project_name: String) -> Result<u128, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.vesting_released.get(project_name).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_vesting_voting_state(
+            &self,
+            project_name: String,
+        ) -> Result<ProjectVotes, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(match self.vesting_voting_state.get(project_name) {
+                Some(value) => value,
+                None => ProjectVotes {
+                    ovr_voted_yes: 0,
+                    ovr_voted_no: 0,
+                    quad_voted_yes: 0,
+                    quad_voted_no: 0,
+                },
+            })
+        }
+
+        #[ink(message)]
+        pub fn release_vested(&mut self, project_name: String) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            let info = self.get_project_info(project_name.clone())?;
+
+            // Any of the co-authors can release the vested budget.
+            if !is_co_author(&info, self.env().caller()) {
+                return Err(Error::YouAreNotTheFather);
+            }
+
+            if !self.vesting_total.contains(project_name.clone()) {
+                return Err(Error::BudgetNotClaimedYet);
+            }
+
+            let vested = self.get_vested_amount(project_name.clone())?;
+
+            let released = self.get_vesting_released(project_name.clone())?;
+
+            if vested <= released {
+                return Err(Error::NoFundsToClaim);
+            }
+
+            self.vesting_released.insert(project_name.clone(), &vested);
+
+            self.pay_out(project_name, &info, vested - released)
+        }
+
+        #[ink(message)]
+        pub fn make_vesting_vote(&mut self, project_name: String, vote: bool) -> Result<(), Error> {
+            // Voting yes is voting to stop the stream, the unvested remainder goes back to the donors.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let vesting = match &info.vesting {
+                Some(value) => value,
+                None => return Err(Error::NoVesting),
+            };
+
+            if !self.vesting_total.contains(project_name.clone()) {
+                return Err(Error::BudgetNotClaimedYet);
+            }
+
+            if self.vesting_cancelled.contains(project_name.clone()) {
+                return Err(Error::VestingCancelled);
+            }
+
+            // Once everything vested there is nothing left to stop.
+            let start = match self.vesting_started.get(project_name.clone()) {
+                Some(value) => value,
+                None => vesting.start,
+            };
+            let current_time = self.env().block_timestamp();
+            if current_time >= start + vesting.duration {
+                return Err(Error::VotingDeadlinePassed);
+            }
+
+            let account = self.env().caller();
+
+            if self.vesting_votes.contains((project_name.clone(), account)) {
+                return Err(Error::AlreadyVoted);
+            }
+
+            let donated = self.get_donated_amount(project_name.clone(), account)?;
+
+            // No donation, no vote.
+            if donated == 0 {
+                return Err(Error::NoFundsDontatedNoVote);
+            }
+
+            let mut voting_state = self.get_vesting_voting_state(project_name.clone())?;
+
+            match vote {
+                true => {
+                    voting_state.ovr_voted_yes += donated;
+                    voting_state.quad_voted_yes += quadratic_weight(&info, donated);
+                }
+                false => {
+                    voting_state.ovr_voted_no += donated;
+                    voting_state.quad_voted_no += quadratic_weight(&info, donated);
+                }
+            }
+
+            self.vesting_voting_state
+                .insert(project_name.clone(), &voting_state);
+            self.vesting_votes
+                .insert((project_name.clone(), account), &vote);
+
+            self.env().emit_event(VestingVoteCast {
+                project_name: project_name.clone(),
+                donor: account,
+                vote,
+                weight: donated,
+            });
+
+            // The stream stops as soon as the threshold and the quorum are met, there is no deadline.
+            // A yes stops the payout here, so a missed quorum always keeps the stream running.
+            let mut info = info;
+            info.voting_rules.default_outcome = false;
+            match self.voting_result(project_name.clone(), &voting_state, &info, Timestamp::MAX) {
+                Ok(true) => (),
+                _ => return Ok(()),
+            }
+
+            let vested = self.get_vested_amount(project_name.clone())?;
+            let total = self
+                .vesting_total
+                .get(project_name.clone())
+                .unwrap_or_default();
+
+            self.vesting_cancelled
+                .insert(project_name.clone(), &current_time);
+
+            self.env().emit_event(VestingCancelled {
+                project_name: project_name.clone(),
+                unvested: total - vested,
+            });
+
+            // The unvested part of the match goes back to the round's pool, not to the donors.
+            let (_, returned) = self.split_unvested(project_name.clone())?;
+            if returned > 0 {
+                let round = match self.project_round.get(project_name.clone()) {
+                    Some(value) => value,
+                    None => return Err(Error::NoMatchedFunds),
+                };
+                let pool = self.get_round_pool(round)?;
+                self.round_pools.insert(round, &(pool + returned));
+
+                self.env().emit_event(MatchingReturned {
+                    project_name,
+                    round,
+                    amount: returned,
+                });
+            }
+            Ok(())
+        }
+
+        fn split_unvested(&self, project_name: String) -> Result<(u128, u128), Error> {
+            // Splits what a stopped stream didn't vest into the donors' part and the match's part.
+            let total = match self.vesting_total.get(project_name.clone()) {
+                Some(value) => value,
+                None => return Ok((0, 0)),
+            };
+            let matched = self
+                .vesting_matched
+                .get(project_name.clone())
+                .unwrap_or_default();
+            let vested = self.get_vested_amount(project_name)?;
+
+            let unvested = total - vested;
+            let returned = mul_div(matched, unvested, total);
+            Ok((unvested - returned, returned))
+        }
+
+        #[ink(message)]
+        pub fn claim_reward(&mut self, project_name: String) -> Result<(), Error> {
+            // Mints the NFT of the donor's reward tier once the author claimed the budget.
+            let info = self.get_project_info(project_name.clone())?;
+
+            let (milestone, _) = self.get_milestone_progress(project_name.clone())?;
+
+            if milestone == 0 {
+                return Err(Error::BudgetNotClaimedYet);
+            }
+
+            let donor = self.env().caller();
+
+            // Refunded donors don't get the perks.
+            if self.get_donor_refunded(project_name.clone(), donor)? {
+                return Err(Error::NoReward);
+            }
+
+            let tier = match self.get_reward_tier(project_name.clone(), donor)? {
+                Some(value) => value,
+                None => return Err(Error::NoReward),
+            };
+
+            if self.get_reward_minted(project_name.clone(), donor)? {
+                return Err(Error::RewardAlreadyClaimed);
+            }
+
+            let collection = match info.reward_collection {
+                Some(value) => value,
+                None => return Err(Error::NoReward),
+            };
+
+            // Make note of the reward, one NFT per donor and project.
+            self.reward_minted
+                .insert((project_name.clone(), donor), &true);
+
+            let id = PSP34Id::Bytes(scale::Encode::encode(&(project_name.clone(), donor)));
+            let result = build_call::<Environment>()
+                .call_type(Call::new().callee(collection).gas_limit(0))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP34_MINT))
+                        .push_arg(donor)
+                        .push_arg(id),
+                )
+                .returns::<Result<(), PSP34Error>>()
+                .fire();
+
+            match result {
+                Ok(Ok(_)) => (),
+                _ => return Err(Error::TransferFailed),
+            }
+
+            self.env().emit_event(RewardMinted {
+                project_name,
+                donor,
+                tier,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_round_count(&self) -> Result<u32, Error> {
+            Ok(*self.round_count)
+        }
+
+        #[ink(message)]
+        pub fn get_round(&self, round: u32) -> Result<MatchingRound, Error> {
+            match self.rounds.get(round) {
+                Some(value) => Ok(value),
+                None => Err(Error::NoSuchRound),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_round_pool(&self, round: u32) -> Result<u128, Error> {
+            match self.round_pools.get(round) {
+                Some(value) => Ok(value),
+                None => Err(Error::NoSuchRound),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_round_finalised(&self, round: u32) -> Result<bool, Error> {
+            let info = self.get_round(round)?;
+
+            let progress = self.round_progress.get(round).unwrap_or_default();
+            Ok(progress as usize >= 2 * info.projects.len())
+        }
+
+        #[ink(message)]
+        pub fn get_project_round(&self, project_name: String) -> Result<Option<u32>, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.project_round.get(project_name))
+        }
+
+        #[ink(message)]
+        pub fn get_matching_donors(&self, project_name: String) -> Result<u32, Error> {
+            if !self.projects.contains(project_name.clone()) {
+                return Err(Error::ProjectDoesntExist);
+            }
+
+            Ok(self.matching_donors.get(project_name).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn get_matched_amount(&self, project_name: String) -> Result<u128, Error> {
+            // Fails until the project's round is finalised.
+            let round = match self.get_project_round(project_name.clone())? {
+                Some(value) => value,
+                None => return Ok(0),
+            };
+
+            if !self.get_round_finalised(round)? {
+                return Err(Error::RoundNotFinalised);
+            }
+
+            Ok(self.matched.get(project_name).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        pub fn create_round(
+            &mut self,
+            start: Timestamp,
+            end: Timestamp,
+            token: Option<AccountId>,
+            projects: Vec<String>,
+        ) -> Result<(), Error> {
+            // Rounds are curated by the owner, a project can take part in one round only.
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if start >= end || end <= self.env().block_timestamp() {
+                return Err(Error::IncorrectRound);
+            }
+
+            if projects.is_empty() || projects.len() > MAX_ROUND_PROJECTS {
+                return Err(Error::IncorrectRound);
+            }
+
+            for (index, project_name) in projects.iter().enumerate() {
+                let info = self.get_project_info(project_name.clone())?;
+
+                if info.token != token {
+                    return Err(Error::WrongCurrency);
+                }
+
+                // Only projects still collecting donations can get matched.
+                if self.env().block_timestamp() >= info.deadline {
+                    return Err(Error::DeadlinePassed);
+                }
+
+                // The author can't claim before the round is finalised, so it ends by the deadline.
+                if end > info.deadline {
+                    return Err(Error::IncorrectRound);
+                }
+
+                if self.get_project_cancelled(project_name.clone())? {
+                    return Err(Error::ProjectCancelled);
+                }
+
+                if self.project_round.contains(project_name)
+                    || projects[..index].contains(project_name)
+                {
+                    return Err(Error::ProjectAlreadyInRound);
+                }
+            }
+
+            let round = *self.round_count;
+            for project_name in projects.iter() {
+                self.project_round.insert(project_name, &round);
+            }
+            self.rounds.insert(
+                round,
+                &MatchingRound {
+                    start,
+                    end,
+                    token,
+                    projects: projects.clone(),
+                },
+            );
+            self.round_pools.insert(round, &0);
+            *self.round_count += 1;
+
+            self.env().emit_event(RoundCreated {
+                round,
+                start,
+                end,
+                projects,
+            });
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        pub fn fund_round(&mut self, round: u32) -> Result<(), Error> {
+            let value = self.env().transferred_value();
+            self.fund(round, value, false)
+        }
+
+        #[ink(message)]
+        pub fn fund_round_tokens(&mut self, round: u32, amount: u128) -> Result<(), Error> {
+            // The sponsor has to approve the contract to spend `amount` of the round's token first.
+            self.fund(round, amount, true)
+        }
+
+        #[ink(message)]
+        pub fn finalise_round(&mut self, round: u32, limit: u32) -> Result<(), Error> {
+            // Sums up the projects' scores first, then splits the pool by them.
+            // Processes at most `limit` steps, call until the round is finalised.
+            let info = self.get_round(round)?;
+
+            if self.env().block_timestamp() < info.end {
+                return Err(Error::RoundNotEnded);
+            }
+
+            let count = info.projects.len() as u32;
+            let start = self.round_progress.get(round).unwrap_or_default();
+            if start >= 2 * count {
+                return Err(Error::RoundFinalised);
+            }
+            let end = core::cmp::min(start.saturating_add(limit), 2 * count);
+
+            let pool = self.get_round_pool(round)?;
+            let mut score = self.round_scores.get(round).unwrap_or_default();
+            let mut matched = self.round_matched.get(round).unwrap_or_default();
+
+            for step in start..end {
+                let project_name = &info.projects[(step % count) as usize];
+                let project_score = self.matching_score(project_name.clone());
+                match step < count {
+                    true => score += project_score,
+                    false => {
+                        let amount = match score {
+                            0 => 0,
+                            _ => mul_div(pool, project_score, score),
+                        };
+                        self.matched.insert(project_name, &amount);
+                        matched += amount;
+                    }
+                }
+            }
+
+            self.round_progress.insert(round, &end);
+            self.round_scores.insert(round, &score);
+            self.round_matched.insert(round, &matched);
+
+            // What's left of the pool after rounding stays in it.
+            if end == 2 * count {
+                self.round_pools.insert(round, &(pool - matched));
+
+                self.env().emit_event(RoundFinalised { round, matched });
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn return_matching(&mut self, project_name: String) -> Result<(), Error> {
+            // The match of a failed or cancelled project goes back to the round's pool.
+            let status = self.get_project_status(project_name.clone())?;
+
+            match status {
+                ProjectStatus::Failed | ProjectStatus::Cancelled => (),
+                _ => return Err(Error::CampaignSuccessfulNoRefunds),
+            }
+
+            let amount = self.get_matched_amount(project_name.clone())?;
+
+            if amount == 0 {
+                return Err(Error::NoMatchedFunds);
+            }
+
+            let round = match self.project_round.get(project_name.clone()) {
+                Some(value) => value,
+                None => return Err(Error::NoMatchedFunds),
+            };
+            let pool = self.get_round_pool(round)?;
+
+            self.matched.insert(project_name.clone(), &0);
+            self.round_pools.insert(round, &(pool + amount));
+
+            self.env().emit_event(MatchingReturned {
+                project_name,
+                round,
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn rollover_pool(&mut self, from_round: u32, to_round: u32) -> Result<(), Error> {
+            // Moves what's left in a finalised round to one that's still open.
+            if self.env().caller() != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if !self.get_round_finalised(from_round)? {
+                return Err(Error::RoundNotFinalised);
+            }
+
+            let from = self.get_round(from_round)?;
+            let to = self.get_round(to_round)?;
+
+            if self.env().block_timestamp() >= to.end {
+                return Err(Error::RoundEnded);
+            }
+
+            if from.token != to.token {
+                return Err(Error::WrongCurrency);
+            }
+
+            let amount = self.get_round_pool(from_round)?;
+            let pool = self.get_round_pool(to_round)?;
+
+            self.round_pools.insert(from_round, &0);
+            self.round_pools.insert(to_round, &(pool + amount));
+
+            self.env().emit_event(RoundFunded {
+                round: to_round,
+                sponsor: self.env().account_id(),
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn withdraw_pool(&mut self, round: u32) -> Result<(), Error> {
+            // Takes out what's left in a finalised round, e.g. when no later round needs it.
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            let owner = self.env().caller();
+            if owner != self.owner_account {
+                return Err(Error::NotOwner);
+            }
+
+            if !self.get_round_finalised(round)? {
+                return Err(Error::RoundNotFinalised);
+            }
+
+            let info = self.get_round(round)?;
+            let amount = self.get_round_pool(round)?;
+
+            if amount == 0 {
+                return Err(Error::NoFundsToClaim);
+            }
+
+            self.transfer_funds(info.token, owner, amount)?;
+            self.round_pools.insert(round, &0);
+
+            self.env().emit_event(PoolWithdrawn {
+                round,
+                owner,
+                amount,
+            });
+            Ok(())
+        }
+
+        fn fund(&mut self, round: u32, value: u128, in_tokens: bool) -> Result<(), Error> {
+            if *self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            let info = self.get_round(round)?;
+
+            if self.env().block_timestamp() >= info.end {
+                return Err(Error::RoundEnded);
+            }
+
+            if info.token.is_some() != in_tokens {
+                return Err(Error::WrongCurrency);
+            }
+
+            let sponsor = self.env().caller();
+            if let Some(token) = info.token {
+                self.transfer_tokens_from(token, sponsor, value)?;
+            }
+
+            let pool = self.get_round_pool(round)?;
+            self.round_pools.insert(round, &(pool + value));
+
+            self.env().emit_event(RoundFunded {
+                round,
+                sponsor,
+                amount: value,
+            });
+            Ok(())
+        }
+
+        fn return_donation(
+            &mut self,
+            project_name: String,
+            info: &ProjectInfo,
+            donor: AccountId,
+            donated: u128,
+            amount: u128,
+        ) -> Result<(), Error> {
+            let budget = self.get_collected_budget(project_name.clone())?;
+
+            let withdrawn = self.get_donor_withdrawn(project_name.clone(), donor)?;
+
+            // Undo the donation, the donor stays in the index.
+            self.donations
+                .insert((project_name.clone(), donor), &(donated - amount));
+            self.budgets
+                .insert(project_name.clone(), &(budget - amount));
+            self.withdrawn
+                .insert((project_name.clone(), donor), &(withdrawn + amount));
+
+            self.update_reward_tier(project_name.clone(), info, donor, donated - amount);
+            self.update_quadratic_budget(project_name.clone(), info, donated, donated - amount);
+            self.update_matching(project_name.clone(), donor, amount, false);
+
+            // The donor's weight in the extension vote goes down with the donation.
+            if let Some((vote, weight)) = self.extension_votes.get((project_name.clone(), donor)) {
+                let removed = core::cmp::min(weight, amount);
+                let mut voting_state = self.get_extension_voting_state(project_name.clone())?;
+                match vote {
+                    true => voting_state.ovr_voted_yes -= removed,
+                    false => voting_state.ovr_voted_no -= removed,
+                }
+                self.extension_voting_state
+                    .insert(project_name.clone(), &voting_state);
+                self.extension_votes
+                    .insert((project_name.clone(), donor), &(vote, weight - removed));
+            }
+
+            // Fewer donors are left to approve the extension.
+            self.approve_extension(project_name);
+
+            match self.transfer_funds(info.token, donor, amount) {
+                Ok(_) => Ok(()),
+                Err(_) => Err(Error::TransferFailed),
+            }
+        }
+
+        fn approve_extension(&mut self, project_name: String) {
+            // A donation-weighted majority of the current donors applies the extension.
+            let extension = match self.extension_proposal.get(project_name.clone()) {
+                Some(value) => value,
+                None => return,
+            };
+
+            if self.deadline_extension.contains(project_name.clone()) {
+                return;
+            }
+
+            let budget = self.budgets.get(project_name.clone()).unwrap_or_default();
+            let voting_state = match self.extension_voting_state.get(project_name.clone()) {
+                Some(value) => value,
+                None => return,
+            };
+
+            if voting_state.ovr_voted_yes * 2 <= budget {
+                return;
+            }
+
+            self.deadline_extension
+                .insert(project_name.clone(), &extension);
+
+            let deadline = match self.get_project_info(project_name.clone()) {
+                Ok(value) => value.deadline,
+                Err(_) => return,
+            };
+            self.env().emit_event(DeadlineExtended {
+                project_name,
+                deadline,
+            });
+        }
+
+        fn remove_delegation(
+            &mut self,
+            project_name: Option<String>,
+            donor: AccountId,
+        ) -> Option<AccountId> {
+            let delegate = self.delegates.get((project_name.clone(), donor))?;
+
+            // The last delegator takes the place of the removed one.
+            let last = match self.delegator_count.get((project_name.clone(), delegate)) {
+                Some(value) => value - 1,
+                None => 0,
+            };
+            let position = match self.delegator_position.get((project_name.clone(), donor)) {
+                Some(value) => value,
+                None => last,
+            };
+            if position != last {
+                if let Some(moved) = self.delegators.get((project_name.clone(), delegate, last)) {
+                    self.delegators
+                        .insert((project_name.clone(), delegate, position), &moved);
+                    self.delegator_position
+                        .insert((project_name.clone(), moved), &position);
+                }
+            }
+
+            self.delegators
+                .remove((project_name.clone(), delegate, last));
+            self.delegator_count
+                .insert((project_name.clone(), delegate), &last);
+            self.delegator_position
+                .remove((project_name.clone(), donor));
+            self.delegates.remove((project_name, donor));
+            Some(delegate)
+        }
+
+        fn collect_delegated_weight(
+            &mut self,
+            project_name: String,
+            info: &ProjectInfo,
+            delegate: AccountId,
+        ) -> (u128, u128) {
+            // Only the donors who delegated to the account directly are counted, delegation
+            // is not transitive. A project delegation takes precedence over the general one.
+            let mut weight = 0;
+            let mut quadratic = 0;
+            for scope in [Some(project_name.clone()), None] {
+                let count = self
+                    .delegator_count
+                    .get((scope.clone(), delegate))
+                    .unwrap_or_default();
+
+                for index in 0..count {
+                    let donor = match self.delegators.get((scope.clone(), delegate, index)) {
+                        Some(value) => value,
+                        None => continue,
+                    };
+
+                    if scope.is_none()
+                        && self.delegates.contains((Some(project_name.clone()), donor))
+                    {
+                        continue;
+                    }
+
+                    // Donors who voted on their own or through another delegate are skipped.
+                    // The weight is free again once that delegate withdrew their vote.
+                    if self.votes.contains((project_name.clone(), donor)) {
+                        continue;
+                    }
+                    match self.delegated_votes.get((project_name.clone(), donor)) {
+                        Some(other)
+                            if other != delegate
+                                && self.votes.contains((project_name.clone(), other)) =>
+                        {
+                            continue
+                        }
+                        _ => (),
+                    }
+
+                    let donated = self
+                        .donations
+                        .get((project_name.clone(), donor))
+                        .unwrap_or_default();
+                    if donated == 0 {
+                        continue;
+                    }
+
+                    self.delegated_votes
+                        .insert((project_name.clone(), donor), &delegate);
+                    weight += donated;
+                    quadratic += quadratic_weight(info, donated);
+                }
+            }
+            (weight, quadratic)
+        }
+
+        fn take_delegated_weight(
+            &mut self,
+            project_name: String,
+            delegate: AccountId,
+            (donated, quadratic): (u128, u128),
+            voting_state: &mut ProjectVotes,
+        ) {
+            // Nothing to take if the delegate withdrew their vote.
+            let vote = match self.votes.get((project_name.clone(), delegate)) {
+                Some(value) => value,
+                None => return,
+            };
+            let (weight, quadratic_weight) =
+                match self.vote_weights.get((project_name.clone(), delegate)) {
+                    Some(value) => value,
+                    None => return,
+                };
+
+            match vote {
+                true => {
+                    voting_state.ovr_voted_yes -= donated;
+                    voting_state.quad_voted_yes -= quadratic;
+                }
+                false => {
+                    voting_state.ovr_voted_no -= donated;
+                    voting_state.quad_voted_no -= quadratic;
+                }
+            }
+            self.vote_weights.insert(
+                (project_name, delegate),
+                &(weight - donated, quadratic_weight - quadratic),
+            );
+        }
+
+        fn vote_weight(
+            &self,
+            project_name: String,
+            account: AccountId,
+            donated: u128,
+        ) -> (u128, u128) {
+            // Votes cast before delegations were introduced weigh as much as the donation.
+            match self.vote_weights.get((project_name, account)) {
+                Some(value) => value,
+                None => (donated, 0),
+            }
+        }
+
+        fn update_quadratic_budget(
+            &mut self,
+            project_name: String,
+            info: &ProjectInfo,
+            donated: u128,
+            new_donated: u128,
+        ) {
+            if !info.quadratic_voting {
+                return;
+            }
+
+            let budget = self
+                .quadratic_budgets
+                .get(project_name.clone())
+                .unwrap_or_default();
+            self.quadratic_budgets.insert(
+                project_name,
+                &(budget - isqrt(donated) + isqrt(new_donated)),
+            );
+        }
+
+        fn update_matching(
+            &mut self,
+            project_name: String,
+            donor: AccountId,
+            amount: u128,
+            added: bool,
+        ) {
+            // Only the donations made during the project's round are matched.
+            let round = match self.project_round.get(project_name.clone()) {
+                Some(value) => value,
+                None => return,
+            };
+            let info = match self.rounds.get(round) {
+                Some(value) => value,
+                None => return,
+            };
+
+            let current_time = self.env().block_timestamp();
+            if current_time < info.start || current_time >= info.end {
+                return;
+            }
+
+            let contribution = self
+                .matching_contributions
+                .get((project_name.clone(), donor))
+                .unwrap_or_default();
+            let new_contribution = match added {
+                true => contribution + amount,
+                false => contribution - core::cmp::min(contribution, amount),
+            };
+
+            let sqrt_sum = self
+                .matching_sqrt_sums
+                .get(project_name.clone())
+                .unwrap_or_default();
+            let total = self
+                .matching_totals
+                .get(project_name.clone())
+                .unwrap_or_default();
+            let mut donors = self
+                .matching_donors
+                .get(project_name.clone())
+                .unwrap_or_default();
+            if contribution == 0 && new_contribution > 0 {
+                donors += 1;
+            }
+            if contribution > 0 && new_contribution == 0 {
+                donors -= 1;
+            }
+
+            self.matching_contributions
+                .insert((project_name.clone(), donor), &new_contribution);
+            self.matching_sqrt_sums.insert(
+                project_name.clone(),
+                &(sqrt_sum - isqrt(contribution) + isqrt(new_contribution)),
+            );
+            self.matching_totals.insert(
+                project_name.clone(),
+                &(total - contribution + new_contribution),
+            );
+            self.matching_donors.insert(project_name, &donors);
+        }
+
+        fn matching_score(&self, project_name: String) -> u128 {
+            // Quadratic funding: the square of the sum of square roots of the contributions,
+            // without the contributions themselves.
+            let sqrt_sum = self
+                .matching_sqrt_sums
+                .get(project_name.clone())
+                .unwrap_or_default();
+            let total = self.matching_totals.get(project_name).unwrap_or_default();
+            sqrt_sum.saturating_mul(sqrt_sum).saturating_sub(total)
+        }
+
+        fn update_reward_tier(
+            &mut self,
+            project_name: String,
+            info: &ProjectInfo,
+            donor: AccountId,
+            donated: u128,
+        ) {
+            // Moves the donor to the highest tier with free supply their total qualifies for.
+            let current = self.reward_tier.get((project_name.clone(), donor));
+
+            let mut best = None;
+            for (index, tier) in info.reward_tiers.iter().enumerate() {
+                let index = index as u32;
+                let taken = self
+                    .reward_tier_taken
+                    .get((project_name.clone(), index))
+                    .unwrap_or_default();
+                if tier.min_amount <= donated && (taken < tier.supply || current == Some(index)) {
+                    best = Some(index);
+                }
+            }
+
+            if best == current {
+                return;
+            }
+
+            if let Some(index) = current {
+                let taken = self
+                    .reward_tier_taken
+                    .get((project_name.clone(), index))
+                    .unwrap_or_default();
+                self.reward_tier_taken
+                    .insert((project_name.clone(), index), &(taken - 1));
+            }
+
+            match best {
+                Some(index) => {
+                    let taken = self
+                        .reward_tier_taken
+                        .get((project_name.clone(), index))
+                        .unwrap_or_default();
+                    self.reward_tier_taken
+                        .insert((project_name.clone(), index), &(taken + 1));
+                    self.reward_tier.insert((project_name, donor), &index);
+                }
+                None => self.reward_tier.remove((project_name, donor)),
+            }
+        }
+
+        fn transfer_funds(
+            &mut self,
+            token: Option<AccountId>,
+            to: AccountId,
+            value: u128,
+        ) -> Result<(), Error> {
+            // Pays out in the currency of the project.
+            let token = match token {
+                Some(value) => value,
+                None => {
+                    return match self.env().transfer(to, value) {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(Error::TransferFailed),
+                    }
+                }
+            };
+
+            let result = build_call::<Environment>()
+                .call_type(Call::new().callee(token).gas_limit(0))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER))
+                        .push_arg(to)
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .fire();
+
+            match result {
+                Ok(Ok(_)) => Ok(()),
+                _ => Err(Error::TransferFailed),
+            }
+        }
+
+        fn transfer_tokens_from(
+            &mut self,
+            token: AccountId,
+            from: AccountId,
+            value: u128,
+        ) -> Result<(), Error> {
+            let result = build_call::<Environment>()
+                .call_type(Call::new().callee(token).gas_limit(0))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER_FROM))
+                        .push_arg(from)
+                        .push_arg(self.env().account_id())
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .fire();
+
+            match result {
+                Ok(Ok(_)) => Ok(()),
+                _ => Err(Error::TransferFailed),
+            }
+        }
+
+        fn extended_deadline(&self, info: &ProjectInfo) -> Timestamp {
+            let mut deadline = info.deadline;
+            for (start, end) in self.deadline_extensions.iter() {
+                if *start >= info.create_time && *start < deadline {
+                    deadline += end - start;
+                }
+            }
+
+            // The ongoing pause keeps pushing the deadline.
+            if *self.paused
+                && *self.extend_deadlines
+                && *self.paused_since >= info.create_time
+                && *self.paused_since < deadline
+            {
+                deadline += self.env().block_timestamp() - *self.paused_since;
+            }
+            deadline
+        }
+
+        fn votes_locked(&self, project_name: String) -> bool {
+            // The result was acted upon, the budget was claimed or a donor got refunded.
+            let claimed = match self.milestone_index.get(project_name.clone()) {
+                Some(value) => value > 0,
+                None => false,
+            };
+            claimed || self.refund_issued.contains(project_name)
+        }
+
+        fn voting_result(
+            &self,
+            project_name: String,
+            voting_state: &ProjectVotes,
+            info: &ProjectInfo,
+            voting_end: Timestamp,
+        ) -> Result<bool, Error> {
+            // Quadratic votes are compared against the quadratic weights of all donors.
+            let (yes, no, budget) = match info.quadratic_voting {
+                true => (
+                    voting_state.quad_voted_yes,
+                    voting_state.quad_voted_no,
+                    self.get_quadratic_budget(project_name)?,
+                ),
+                false => (
+                    voting_state.ovr_voted_yes,
+                    voting_state.ovr_voted_no,
+                    self.get_collected_budget(project_name)?,
+                ),
+            };
+
+            // Yes votes have to exceed the threshold, the votes cast have to reach the quorum.
+            // Both are rounded so that no percentage of the budget has to be multiplied out.
+            let rules = &info.voting_rules;
+            let threshold = mul_div(budget, rules.threshold as u128, 100);
+            let quorum = mul_div(budget, rules.quorum as u128, 100)
+                + match (budget % 100) * rules.quorum as u128 % 100 {
+                    0 => 0,
+                    _ => 1,
+                };
+            let voted = yes + no;
+
+            // The result is known early if the votes still missing can't change it.
+            if yes > threshold && (voted >= quorum || rules.default_outcome) {
+                return Ok(true);
+            }
+            if budget - no <= threshold && (no >= quorum || !rules.default_outcome) {
+                return Ok(false);
+            }
+
+            // We treat reaching the deadline as the default result if the quorum was missed,
+            // and as a negative one otherwise.
+            if voting_end < self.env().block_timestamp() {
+                if voted < quorum {
+                    return Ok(rules.default_outcome);
+                }
+                return Ok(false);
+            }
+            Err(Error::CampaignResultUnknown)
+        }
+
+        fn current_voting_result(
+            &self,
+            project_name: String,
+            info: &ProjectInfo,
+        ) -> Result<bool, Error> {
+            // Flexible campaigns without a vote release every tranche.
+            if matches!(info.funding_mode, FundingMode::Flexible) {
+                return Ok(true);
+            }
+
+            // Result of the voting on the next tranche of the budget to be released.
+            if info.milestones.is_empty() {
+                return self.get_project_voting_result(project_name);
+            }
+
+            let (milestone, _) = self.get_milestone_progress(project_name.clone())?;
+
+            // Every milestone was approved and released.
+            if milestone as usize >= info.milestones.len() {
+                return Ok(true);
+            }
+
+            self.get_milestone_voting_result(project_name, milestone)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::crowdfund::isqrt;
+    use crate::crowdfund::mul_div;
+    use crate::crowdfund::Crowdfund;
+    use crate::crowdfund::DonationSummary;
+    use crate::crowdfund::Error;
+    use crate::crowdfund::FundingMode;
+    use crate::crowdfund::Milestone;
+    use crate::crowdfund::ProjectInfo;
+    use crate::crowdfund::ProjectInfoV0;
+    use crate::crowdfund::ProjectOptions;
+    use crate::crowdfund::ProjectStatus;
+    use crate::crowdfund::ProjectVotes;
+    use crate::crowdfund::ProjectVotesV0;
+    use crate::crowdfund::RewardTier;
+    use crate::crowdfund::StretchGoal;
+    use crate::crowdfund::VestingSchedule;
+    use crate::crowdfund::VotingBounds;
+    use crate::crowdfund::VotingRules;
+    use crate::crowdfund::{AuthorAction, Beneficiary};
+    use crate::crowdfund::{
+        BudgetClaimed, DeadlineExtended, DonationMade, DonationWithdrawn, FeePaid,
+        MatchingReturned, PoolWithdrawn, ProjectCreated, RefundIssued, VoteCast,
+    };
+
+    use ink_env::block_timestamp;
+    use ink_env::AccountId;
+    use ink_env::{test, DefaultEnvironment};
+    use ink_lang as ink;
+    use ink_primitives::Key;
+    use ink_storage::traits::{
+        allocate_spread_root, pull_spread_root, push_spread_root, SpreadAllocate, SpreadLayout,
+    };
+    use ink_storage::Mapping;
+
+    type Event = <Crowdfund as ink::reflect::ContractEventBase>::Type;
+
+    fn recorded_events() -> Vec<Event> {
+        test::recorded_events()
+            .map(|event| {
+                <Event as scale::Decode>::decode(&mut &event.data[..])
+                    .expect("encountered invalid contract event data buffer")
+            })
+            .collect()
+    }
+
+    fn advance_to(timestamp: u64) {
+        // advance blocks until the given time passes
+        loop {
+            let t = block_timestamp::<DefaultEnvironment>();
+            if t >= timestamp {
+                break;
+            }
+            test::advance_block::<DefaultEnvironment>();
+        }
+    }
+
+    fn milestones(shares: &[u8]) -> ProjectOptions {
+        ProjectOptions {
+            milestones: shares
+                .iter()
+                .map(|share| Milestone {
+                    share: *share,
+                    description: String::from("Next step."),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[ink::test]
+    fn test_create_project() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                10,
+                Box::default(),
+            )
+            .ok();
+        assert_eq!(contract.get_projects(0, 10), Ok(vec![String::from("Doll")]));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                6,
+                12,
+                Box::default(),
+            )
+            .ok();
+        assert_eq!(
+            contract.get_projects(0, 10),
+            Ok(vec![String::from("Doll"), String::from("Toy car")])
+        );
+
+        assert_eq!(
+            contract.get_project_info(String::from("Doll")),
+            Ok(ProjectInfo {
+                description: String::from("I want a doll."),
+                author: accs.alice,
+                create_time: 0,
+                deadline: 5,
+                goal: 10,
+                voting_length: 3,
+                fee_percent: 0,
+                token: None,
+                reward_tiers: vec![],
+                reward_collection: None,
+                milestones: vec![],
+                withdrawal_lock_in: None,
+                voting_rules: VotingRules {
+                    threshold: 50,
+                    quorum: 0,
+                    default_outcome: false,
+                },
+                quadratic_voting: false,
+                funding_mode: FundingMode::AllOrNothing,
+                stretch_goals: vec![],
+                stretch_goal_votes: false,
+                max_budget: None,
+                refund_excess: false,
+                min_donation: None,
+                max_donation_per_account: None,
+                private: false,
+                beneficiaries: vec![],
+                approvals_required: 0,
+                vesting: None,
+            })
+        );
+
+        assert_eq!(
+            contract.get_project_info(String::from("Toy car")),
+            Ok(ProjectInfo {
+                description: String::from("I want a toy car."),
+                author: accs.bob,
+                create_time: 0,
+                deadline: 6,
+                goal: 12,
+                voting_length: 3,
+                fee_percent: 0,
+                token: None,
+                reward_tiers: vec![],
+                reward_collection: None,
+                milestones: vec![],
+                withdrawal_lock_in: None,
+                voting_rules: VotingRules {
+                    threshold: 50,
+                    quorum: 0,
+                    default_outcome: false,
+                },
+                quadratic_voting: false,
+                funding_mode: FundingMode::AllOrNothing,
+                stretch_goals: vec![],
+                stretch_goal_votes: false,
+                max_budget: None,
+                refund_excess: false,
+                min_donation: None,
+                max_donation_per_account: None,
+                private: false,
+                beneficiaries: vec![],
+                approvals_required: 0,
+                vesting: None,
+            })
+        );
+    }
+
+    #[ink::test]
+    fn test_donation_balances() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                1000,
+                500,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                1200,
+                600,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(350);
+        contract.make_donation(String::from("Doll")).ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        test::set_value_transferred::<DefaultEnvironment>(450);
+        contract.make_donation(String::from("Toy car")).ok();
+
+        assert_eq!(
+            contract.get_donated_amount(String::from("Doll"), accs.charlie),
+            Ok(350)
+        );
+        assert_eq!(
+            contract.get_donated_amount(String::from("Toy car"), accs.django),
+            Ok(450)
+        );
+        assert_eq!(
+            contract.get_donated_amount(String::from("Toy car"), accs.charlie),
+            Ok(0)
+        );
+        assert_eq!(
+            contract.get_donated_amount(String::from("Doll"), accs.django),
+            Ok(0)
+        );
+
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(350));
+        assert_eq!(
+            contract.get_collected_budget(String::from("Toy car")),
+            Ok(450)
+        );
+    }
+
+    #[ink::test]
+    fn test_goal_not_reached() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                1000,
+                Box::default(),
+            )
+            .ok(); // deadline = 5
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(350);
+        contract.make_donation(String::from("Doll")).ok();
+
+        // advance blocks until the deadline passes
+        loop {
+            let t = block_timestamp::<DefaultEnvironment>();
+            if t >= 5 {
+                break;
+            }
+            test::advance_block::<DefaultEnvironment>();
+        }
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::GoalNotReached)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_donor_refunded(String::from("Doll"), accs.bob),
+            Ok(true)
+        );
+        assert_eq!(
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::NoFundsToRefund)
+        );
+    }
+
+    #[ink::test]
+    fn test_events_successful_campaign() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 10, accs.eve);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+
+        // advance blocks until the deadline passes
+        loop {
+            let t = block_timestamp::<DefaultEnvironment>();
+            if t >= 5 {
+                break;
+            }
+            test::advance_block::<DefaultEnvironment>();
+        }
+
+        contract.make_vote(String::from("Doll"), true).ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+
+        let events = recorded_events();
+        assert_eq!(events.len(), 5);
+        match &events[0] {
+            Event::ProjectCreated(event) => assert_eq!(
+                event,
+                &ProjectCreated {
+                    project_name: String::from("Doll"),
+                    author: accs.alice,
+                    deadline: 5,
+                    goal: 100,
+                }
+            ),
+            _ => panic!("expected ProjectCreated event"),
+        }
+        match &events[1] {
+            Event::DonationMade(event) => assert_eq!(
+                event,
+                &DonationMade {
+                    project_name: String::from("Doll"),
+                    donor: accs.bob,
+                    amount: 100,
+                }
+            ),
+            _ => panic!("expected DonationMade event"),
+        }
+        match &events[2] {
+            Event::VoteCast(event) => assert_eq!(
+                event,
+                &VoteCast {
+                    project_name: String::from("Doll"),
+                    donor: accs.bob,
+                    vote: true,
+                    weight: 100,
+                }
+            ),
+            _ => panic!("expected VoteCast event"),
+        }
+        match &events[3] {
+            Event::FeePaid(event) => assert_eq!(
+                event,
+                &FeePaid {
+                    project_name: String::from("Doll"),
+                    owner: accs.eve,
+                    amount: 10,
+                }
+            ),
+            _ => panic!("expected FeePaid event"),
+        }
+        match &events[4] {
+            Event::BudgetClaimed(event) => assert_eq!(
+                event,
+                &BudgetClaimed {
+                    project_name: String::from("Doll"),
+                    author: accs.alice,
+                    amount: 90,
+                }
+            ),
+            _ => panic!("expected BudgetClaimed event"),
+        }
+    }
+
+    #[ink::test]
+    fn test_events_refund() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                1000,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(350);
+        contract.make_donation(String::from("Doll")).ok();
+
+        // failed donations and refunds don't emit anything
+        assert_eq!(
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::DeadlineNotPassedYet)
+        );
+        assert_eq!(recorded_events().len(), 2);
+
+        // advance blocks until the deadline passes
+        loop {
+            let t = block_timestamp::<DefaultEnvironment>();
+            if t >= 5 {
+                break;
+            }
+            test::advance_block::<DefaultEnvironment>();
+        }
+
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+
+        let events = recorded_events();
+        assert_eq!(events.len(), 3);
+        match &events[2] {
+            Event::RefundIssued(event) => assert_eq!(
+                event,
+                &RefundIssued {
+                    project_name: String::from("Doll"),
+                    donor: accs.bob,
+                    amount: 350,
+                }
+            ),
+            _ => panic!("expected RefundIssued event"),
+        }
+    }
+
+    #[ink::test]
+    fn test_milestones_validation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(milestones(&[40, 40]))
+            ),
+            Err(Error::IncorrectMilestoneShares)
+        );
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(milestones(&[100, 0]))
+            ),
+            Err(Error::IncorrectMilestoneShares)
+        );
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(milestones(&[10; 11]))
+            ),
+            Err(Error::TooManyMilestones)
+        );
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(milestones(&[40, 60]))
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .milestones,
+            milestones(&[40, 60]).milestones
+        );
+        assert_eq!(
+            contract.start_milestone_vote(String::from("Doll")),
+            Err(Error::MilestoneVoteAlreadyStarted)
+        );
+    }
+
+    macro_rules! milestone_tests {
+        ($($name:ident: $second_vote:expr,)*) => {
+        $(
+            #[ink::test]
+            fn $name() {
+                let accs = test::default_accounts::<DefaultEnvironment>();
+                test::set_caller::<DefaultEnvironment>(accs.alice);
+                let mut contract = Crowdfund::new(3, 0, accs.alice);
+                contract.create_project(String::from("Doll"), String::from("I want a doll."), 5, 100, Box::new(milestones(&[40, 60]))).ok();
+
+                test::set_caller::<DefaultEnvironment>(accs.bob);
+                test::set_value_transferred::<DefaultEnvironment>(30);
+                contract.make_donation(String::from("Doll")).ok();
+
+                test::set_caller::<DefaultEnvironment>(accs.charlie);
+                test::set_value_transferred::<DefaultEnvironment>(70);
+                contract.make_donation(String::from("Doll")).ok();
+
+                advance_to(5);
+
+                // the campaign voting approves the first milestone
+                assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+
+                test::set_caller::<DefaultEnvironment>(accs.alice);
+                assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+                assert_eq!(contract.get_milestone_progress(String::from("Doll")), Ok((1, 40)));
+                assert_eq!(contract.get_author_claimed(String::from("Doll")), Ok(false));
+                assert_eq!(contract.claim_budget(String::from("Doll")), Err(Error::CampaignResultUnknown));
+
+                // the next milestone is pending until someone opens its voting
+                test::set_caller::<DefaultEnvironment>(accs.bob);
+                assert_eq!(contract.refund_donation(String::from("Doll")), Err(Error::CampaignResultUnknown));
+                assert_eq!(contract.make_milestone_vote(String::from("Doll"), true), Err(Error::MilestoneVoteNotStarted));
+
+                test::set_caller::<DefaultEnvironment>(accs.django);
+                assert_eq!(contract.start_milestone_vote(String::from("Doll")), Err(Error::NoFundsDontatedNoVote));
+
+                test::set_caller::<DefaultEnvironment>(accs.bob);
+                assert_eq!(contract.start_milestone_vote(String::from("Doll")), Ok(()));
+                assert_eq!(contract.start_milestone_vote(String::from("Doll")), Err(Error::MilestoneVoteAlreadyStarted));
+                assert_eq!(contract.make_milestone_vote(String::from("Doll"), !$second_vote), Ok(()));
+                assert_eq!(contract.make_milestone_vote(String::from("Doll"), $second_vote), Err(Error::AlreadyVoted));
+
+                test::set_caller::<DefaultEnvironment>(accs.charlie);
+                assert_eq!(contract.make_milestone_vote(String::from("Doll"), $second_vote), Ok(()));
+                assert_eq!(contract.get_milestone_vote(String::from("Doll"), 1, accs.charlie), Ok($second_vote));
+                assert_eq!(contract.get_milestone_voting_result(String::from("Doll"), 1), Ok($second_vote));
+
+                match $second_vote {
+                    true => {
+                        test::set_caller::<DefaultEnvironment>(accs.alice);
+                        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+                        assert_eq!(contract.get_milestone_progress(String::from("Doll")), Ok((2, 100)));
+                        assert_eq!(contract.get_author_claimed(String::from("Doll")), Ok(true));
+                        assert_eq!(contract.claim_budget(String::from("Doll")), Err(Error::NoFundsToClaim));
+
+                        test::set_caller::<DefaultEnvironment>(accs.bob);
+                        assert_eq!(contract.refund_donation(String::from("Doll")), Err(Error::CampaignSuccessfulNoRefunds));
+                        assert_eq!(contract.start_milestone_vote(String::from("Doll")), Err(Error::AllMilestonesReleased));
+                    },
+                    false => {
+                        test::set_caller::<DefaultEnvironment>(accs.alice);
+                        assert_eq!(contract.claim_budget(String::from("Doll")), Err(Error::CampaignUnsuccessfulNoClaims));
+
+                        // donors get back their part of the unreleased 60%
+                        test::set_caller::<DefaultEnvironment>(accs.bob);
+                        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+                        test::set_caller::<DefaultEnvironment>(accs.charlie);
+                        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+
+                        let refunds: Vec<(AccountId, u128)> = recorded_events()
+                            .into_iter()
+                            .filter_map(|event| match event {
+                                Event::RefundIssued(event) => Some((event.donor, event.amount)),
+                                _ => None,
+                            })
+                            .collect();
+                        assert_eq!(refunds, vec![(accs.bob, 18), (accs.charlie, 42)]);
+                    },
+                }
+            }
+        )*
+        }
+    }
+
+    milestone_tests! {
+        test_milestones_all_released: true,
+        test_milestones_refund_remainder: false,
+    }
+
+    #[ink::test]
+    fn test_admin_terms() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 10, accs.eve);
+
+        assert_eq!(contract.set_fee_percent(20), Err(Error::NotOwner));
+        assert_eq!(contract.set_voting_length(5), Err(Error::NotOwner));
+
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        assert_eq!(
+            contract.set_fee_percent(101),
+            Err(Error::IncorrectFeePercentage)
+        );
+        assert_eq!(
+            contract.set_voting_length(91 * 24 * 60 * 60 * 1000),
+            Err(Error::IncorrectVotingLength)
+        );
+        assert_eq!(contract.set_fee_percent(20), Ok(()));
+        assert_eq!(contract.set_voting_length(100), Ok(()));
+        assert_eq!(contract.get_static_info(), Ok((100, 20, accs.eve)));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        // the running campaign keeps its terms
+        let info = contract.get_project_info(String::from("Doll")).unwrap();
+        assert_eq!((info.voting_length, info.fee_percent), (3, 10));
+        let info = contract.get_project_info(String::from("Toy car")).unwrap();
+        assert_eq!((info.voting_length, info.fee_percent), (100, 20));
+
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+        contract.make_donation(String::from("Toy car")).ok();
+
+        advance_to(12);
+
+        // voting on the first project is over, the second one is still open
+        assert_eq!(
+            contract.make_vote(String::from("Doll"), true),
+            Err(Error::VotingDeadlinePassed)
+        );
+        assert_eq!(contract.make_vote(String::from("Toy car"), true), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.claim_budget(String::from("Toy car")), Ok(()));
+        let fees: Vec<u128> = recorded_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::FeePaid(event) => Some(event.amount),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fees, vec![20]);
+    }
+
+    #[ink::test]
+    fn test_admin_ownership() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 10, accs.alice);
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.propose_owner(accs.bob), Err(Error::NotOwner));
+        assert_eq!(contract.accept_ownership(), Err(Error::NotPendingOwner));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.propose_owner(accs.bob), Ok(()));
+        assert_eq!(contract.get_pending_owner(), Ok(Some(accs.bob)));
+
+        // the ownership doesn't change until the proposal is accepted
+        assert_eq!(contract.get_static_info(), Ok((3, 10, accs.alice)));
+
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.accept_ownership(), Err(Error::NotPendingOwner));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.accept_ownership(), Ok(()));
+        assert_eq!(contract.get_static_info(), Ok((3, 10, accs.bob)));
+        assert_eq!(contract.get_pending_owner(), Ok(None));
+        assert_eq!(contract.set_fee_percent(5), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.set_fee_percent(5), Err(Error::NotOwner));
+    }
+
+    #[ink::test]
+    fn test_pause() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                1000,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(350);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(contract.pause(false), Err(Error::NotOwner));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.unpause(), Err(Error::ContractNotPaused));
+        assert_eq!(contract.pause(false), Ok(()));
+        assert_eq!(contract.pause(false), Err(Error::ContractPaused));
+        assert_eq!(contract.get_paused(), Ok(true));
+        assert_eq!(
+            contract.create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                5,
+                1000,
+                Box::default()
+            ),
+            Err(Error::ContractPaused)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::ContractPaused)
+        );
+
+        advance_to(5);
+
+        // refunds are still possible
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::ContractPaused)
+        );
+        assert_eq!(contract.unpause(), Ok(()));
+        assert_eq!(contract.get_paused(), Ok(false));
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::GoalNotReached)
+        );
+    }
+
+    #[ink::test]
+    fn test_pause_extends_deadlines() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                10,
+                1000,
+                Box::default(),
+            )
+            .ok();
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                5,
+                1000,
+                Box::default(),
+            )
+            .ok();
+
+        advance_to(6);
+        assert_eq!(contract.pause(true), Ok(()));
+
+        // the ongoing pause pushes the deadline of the running campaign only
+        advance_to(12);
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            16
+        );
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Toy car"))
+                .unwrap()
+                .deadline,
+            5
+        );
+
+        assert_eq!(contract.unpause(), Ok(()));
+        advance_to(18);
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            16
+        );
+
+        // a pause without extension doesn't change anything
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract
+            .create_project(
+                String::from("Teddy bear"),
+                String::from("I want a teddy bear."),
+                30,
+                1000,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.pause(false), Ok(()));
+        advance_to(24);
+        assert_eq!(contract.unpause(), Ok(()));
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Teddy bear"))
+                .unwrap()
+                .deadline,
+            30
+        );
+
+        // only so many pauses can extend the deadlines
+        for _ in 1..20 {
+            contract.pause(true).ok();
+            contract.unpause().ok();
+        }
+        assert_eq!(contract.pause(true), Err(Error::TooManyPauses));
+        assert_eq!(contract.pause(false), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_upgrade_only_owner() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        assert_eq!(contract.get_storage_version(), Ok(1));
+        assert_eq!(contract.migrate(10), Err(Error::MigrationNotNeeded));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.upgrade([0x01; 32]), Err(Error::NotOwner));
+        assert_eq!(contract.migrate(10), Err(Error::NotOwner));
+    }
+
+    // Storage layout of the first deployment, before any upgrade.
+    #[derive(SpreadLayout, SpreadAllocate)]
+    struct BaselineStorage {
+        voting_length: u64,
+        fee_percent: u8,
+        owner_account: AccountId,
+        projects: Mapping<String, ProjectInfoV0>,
+        budgets: Mapping<String, u128>,
+        voting_state: Mapping<String, ProjectVotesV0>,
+        claimed: Mapping<String, bool>,
+        donations: Mapping<(String, AccountId), u128>,
+        votes: Mapping<(String, AccountId), bool>,
+        refunded: Mapping<(String, AccountId), bool>,
+        project_names: Vec<String>,
+    }
+
+    fn baseline_contract(
+        accs: &test::DefaultAccounts<DefaultEnvironment>,
+        names: &[&str],
+    ) -> Crowdfund {
+        // Writes the storage of the first deployment and loads the current contract from it.
+        let root_key = Key::from([0x00; 32]);
+        let mut baseline: BaselineStorage = allocate_spread_root(&root_key);
+        baseline.voting_length = 3;
+        baseline.fee_percent = 10;
+        baseline.owner_account = accs.alice;
+        for name in names.iter() {
+            let project_name = String::from(*name);
+            baseline.projects.insert(
+                &project_name,
+                &ProjectInfoV0 {
+                    description: String::from("I want a toy."),
+                    author: accs.alice,
+                    create_time: 0,
+                    deadline: 5,
+                    goal: 100,
+                },
+            );
+            baseline.budgets.insert(&project_name, &0);
+            baseline.voting_state.insert(
+                &project_name,
+                &ProjectVotesV0 {
+                    ovr_voted_yes: 0,
+                    ovr_voted_no: 0,
+                },
+            );
+            baseline.claimed.insert(&project_name, &false);
+            baseline.project_names.push(project_name);
+        }
+
+        // Bob donated to the first project.
+        baseline.budgets.insert(String::from(names[0]), &100);
+        baseline
+            .donations
+            .insert((String::from(names[0]), accs.bob), &100);
+
+        push_spread_root(&baseline, &root_key);
+        pull_spread_root(&root_key)
+    }
+
+    #[ink::test]
+    fn test_migrate_from_baseline() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = baseline_contract(&accs, &["Doll", "Toy car", "Teddy bear"]);
+
+        // the fields appended since then read as their defaults
+        assert_eq!(contract.get_static_info(), Ok((3, 10, accs.alice)));
+        assert_eq!(contract.get_storage_version(), Ok(0));
+        assert_eq!(contract.get_paused(), Ok(false));
+        assert_eq!(contract.get_pending_owner(), Ok(None));
+        assert_eq!(contract.get_voting_bounds(), Ok(VotingBounds::default()));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.migrate(2), Err(Error::NotOwner));
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.migrate(2), Ok(()));
+        assert_eq!(
+            contract.set_voting_length(6),
+            Err(Error::MigrationInProgress)
+        );
+        assert_eq!(
+            contract.set_fee_percent(20),
+            Err(Error::MigrationInProgress)
+        );
+        assert_eq!(
+            contract.set_voting_bounds(VotingBounds::default()),
+            Err(Error::MigrationInProgress)
+        );
+        assert_eq!(contract.get_storage_version(), Ok(0));
+        assert_eq!(
+            contract.create_project(
+                String::from("Kite"),
+                String::from("I want a kite."),
+                5,
+                100,
+                Box::default()
+            ),
+            Err(Error::MigrationInProgress)
+        );
+        assert_eq!(contract.migrate(2), Ok(()));
+        assert_eq!(contract.get_storage_version(), Ok(1));
+        assert_eq!(contract.migrate(2), Err(Error::MigrationNotNeeded));
+
+        let names = ["Doll", "Toy car", "Teddy bear"];
+        assert_eq!(contract.get_project_count(), Ok(3));
+        assert_eq!(
+            contract.get_projects(0, 10),
+            Ok(names.iter().map(|name| String::from(*name)).collect())
+        );
+        assert_eq!(
+            contract.get_projects_by_author(accs.alice, 0, 10),
+            Ok(names.iter().map(|name| String::from(*name)).collect())
+        );
+        for name in names.iter() {
+            assert_eq!(
+                contract.get_project_info(String::from(*name)),
+                Ok(ProjectInfo {
+                    description: String::from("I want a toy."),
+                    author: accs.alice,
+                    create_time: 0,
+                    deadline: 5,
+                    goal: 100,
+                    voting_length: 3,
+                    fee_percent: 10,
+                    token: None,
+                    reward_tiers: vec![],
+                    reward_collection: None,
+                    milestones: vec![],
+                    withdrawal_lock_in: None,
+                    voting_rules: VotingRules {
+                        threshold: 50,
+                        quorum: 0,
+                        default_outcome: false,
+                    },
+                    quadratic_voting: false,
+                    funding_mode: FundingMode::AllOrNothing,
+                    stretch_goals: vec![],
+                    stretch_goal_votes: false,
+                    max_budget: None,
+                    refund_excess: false,
+                    min_donation: None,
+                    max_donation_per_account: None,
+                    private: false,
+                    beneficiaries: vec![],
+                    approvals_required: 0,
+                    vesting: None,
+                })
+            );
+        }
+
+        // the migrated project works as before, new ones go after it
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(100));
+
+        // legacy donors add their donations to the index themselves
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.get_donations_by_account(accs.bob, 0, 10),
+            Ok(vec![])
+        );
+        assert_eq!(
+            contract.delegate_vote(None, accs.charlie),
+            Err(Error::NoFundsDontatedNoVote)
+        );
+        assert_eq!(
+            contract.index_legacy_donation(String::from("Toy car")),
+            Err(Error::NoLegacyDonation)
+        );
+        assert_eq!(contract.index_legacy_donation(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.index_legacy_donation(String::from("Doll")),
+            Err(Error::NoLegacyDonation)
+        );
+        assert_eq!(
+            contract.get_donations_by_account(accs.bob, 0, 10),
+            Ok(vec![DonationSummary {
+                project_name: String::from("Doll"),
+                amount: 100,
+                refunded: false,
+                vote: None,
+            }])
+        );
+        assert_eq!(contract.delegate_vote(None, accs.charlie), Ok(()));
+        assert_eq!(contract.revoke_delegation(None), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_milestone_progress(String::from("Doll")),
+            Ok((1, 100))
+        );
+        contract
+            .create_project(
+                String::from("Kite"),
+                String::from("I want a kite."),
+                20,
+                100,
+                Box::default(),
+            )
+            .ok();
+        assert_eq!(contract.get_projects(3, 10), Ok(vec![String::from("Kite")]));
+        assert_eq!(
+            contract.get_projects_by_author(accs.alice, 3, 10),
+            Ok(vec![String::from("Kite")])
+        );
+    }
+
+    #[ink::test]
+    fn test_projects_pagination() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        assert_eq!(contract.get_project_count(), Ok(0));
+        assert_eq!(contract.get_projects(0, 10), Ok(vec![]));
+
+        let names = ["Doll", "Toy car", "Teddy bear", "Kite", "Yo-yo"];
+        for name in names.iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::default(),
+                )
+                .ok();
+        }
+
+        assert_eq!(contract.get_project_count(), Ok(5));
+        assert_eq!(
+            contract.get_projects(0, 2),
+            Ok(vec![String::from("Doll"), String::from("Toy car")])
+        );
+        assert_eq!(
+            contract.get_projects(2, 2),
+            Ok(vec![String::from("Teddy bear"), String::from("Kite")])
+        );
+        assert_eq!(contract.get_projects(4, 2), Ok(vec![String::from("Yo-yo")]));
+        assert_eq!(contract.get_projects(5, 2), Ok(vec![]));
+        assert_eq!(contract.get_projects(u32::MAX, u32::MAX), Ok(vec![]));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(40);
+        contract.make_donation(String::from("Toy car")).ok();
+
+        let summaries = contract.get_projects_summary(1, 2).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].project_name, String::from("Toy car"));
+        assert_eq!(
+            summaries[0].info,
+            contract.get_project_info(String::from("Toy car")).unwrap()
+        );
+        assert_eq!(summaries[0].budget, 40);
+        assert_eq!(
+            summaries[0].votes,
+            ProjectVotes {
+                ovr_voted_yes: 0,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            }
+        );
+        assert_eq!(summaries[1].project_name, String::from("Teddy bear"));
+        assert_eq!(summaries[1].budget, 0);
+    }
+
+    #[ink::test]
+    fn test_account_indexes() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        for name in ["Doll", "Kite"].iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::default(),
+                )
+                .ok();
+        }
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        assert_eq!(contract.get_author_project_count(accs.alice), Ok(2));
+        assert_eq!(
+            contract.get_projects_by_author(accs.alice, 0, 10),
+            Ok(vec![String::from("Doll"), String::from("Kite")])
+        );
+        assert_eq!(
+            contract.get_projects_by_author(accs.alice, 1, 10),
+            Ok(vec![String::from("Kite")])
+        );
+        assert_eq!(
+            contract.get_projects_by_author(accs.bob, 0, 10),
+            Ok(vec![String::from("Toy car")])
+        );
+        assert_eq!(
+            contract.get_projects_by_author(accs.charlie, 0, 10),
+            Ok(vec![])
+        );
+
+        // repeated donations are indexed once
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+        test::set_value_transferred::<DefaultEnvironment>(30);
+        contract.make_donation(String::from("Kite")).ok();
+        contract.make_donation(String::from("Kite")).ok();
+        assert_eq!(contract.get_donation_count(accs.charlie), Ok(2));
+
+        advance_to(5);
+        contract.make_vote(String::from("Doll"), false).ok();
+        contract.refund_donation(String::from("Doll")).ok();
+
+        assert_eq!(
+            contract.get_donations_by_account(accs.charlie, 0, 10),
+            Ok(vec![
+                DonationSummary {
+                    project_name: String::from("Doll"),
+                    amount: 100,
+                    refunded: true,
+                    vote: Some(false),
+                },
+                DonationSummary {
+                    project_name: String::from("Kite"),
+                    amount: 60,
+                    refunded: false,
+                    vote: None,
+                },
+            ])
+        );
+        assert_eq!(
+            contract.get_donations_by_account(accs.django, 0, 10),
+            Ok(vec![])
+        );
+    }
+
+    #[ink::test]
+    fn test_token_project_currency() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    token: Some(accs.frank),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .token,
+            Some(accs.frank)
+        );
+
+        // each project accepts only its own currency
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::WrongCurrency)
+        );
+        assert_eq!(
+            contract.make_token_donation(String::from("Toy car"), 100),
+            Err(Error::WrongCurrency)
+        );
+        assert_eq!(contract.make_donation(String::from("Toy car")), Ok(()));
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(0));
+    }
+
+    fn tiers(limits: &[(u128, u32)]) -> Vec<RewardTier> {
+        limits
+            .iter()
+            .map(|(min_amount, supply)| RewardTier {
+                min_amount: *min_amount,
+                supply: *supply,
+                uri: String::from("ipfs://perk"),
+            })
+            .collect()
+    }
+
+    #[ink::test]
+    fn test_reward_tiers_validation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+
+        for options in [
+            ProjectOptions {
+                reward_tiers: tiers(&[(10, 1), (10, 1)]),
+                reward_collection: Some(AccountId::from([0x07; 32])),
+                ..Default::default()
+            },
+            ProjectOptions {
+                reward_tiers: tiers(&[(20, 1), (10, 1)]),
+                reward_collection: Some(AccountId::from([0x07; 32])),
+                ..Default::default()
+            },
+            ProjectOptions {
+                reward_tiers: tiers(&[(10, 0)]),
+                reward_collection: Some(AccountId::from([0x07; 32])),
+                ..Default::default()
+            },
+            ProjectOptions {
+                reward_tiers: tiers(&[(10, 1)]),
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                contract.create_project(
+                    String::from("Doll"),
+                    String::from("I want a doll."),
+                    5,
+                    100,
+                    Box::new(options)
+                ),
+                Err(Error::IncorrectRewardTiers)
+            );
+        }
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    reward_tiers: tiers(&[(1, 1); 11]),
+                    reward_collection: Some(AccountId::from([0x07; 32])),
+                    ..Default::default()
+                })
+            ),
+            Err(Error::TooManyRewardTiers)
+        );
+    }
+
+    #[ink::test]
+    fn test_reward_tiers_supply() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    reward_tiers: tiers(&[(10, 2), (50, 1)]),
+                    reward_collection: Some(AccountId::from([0x07; 32])),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(5);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_reward_tier(String::from("Doll"), accs.bob),
+            Ok(None)
+        );
+
+        // topping up moves the donor to a higher tier
+        test::set_value_transferred::<DefaultEnvironment>(5);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_reward_tier(String::from("Doll"), accs.bob),
+            Ok(Some(0))
+        );
+        test::set_value_transferred::<DefaultEnvironment>(40);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_reward_tier(String::from("Doll"), accs.bob),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            contract.get_reward_tier_taken(String::from("Doll"), 0),
+            Ok(0)
+        );
+        assert_eq!(
+            contract.get_reward_tier_taken(String::from("Doll"), 1),
+            Ok(1)
+        );
+
+        // the top tier is sold out, so the next best one is reserved
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(60);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_reward_tier(String::from("Doll"), accs.charlie),
+            Ok(Some(0))
+        );
+        assert_eq!(
+            contract.get_reward_tier_taken(String::from("Doll"), 0),
+            Ok(1)
+        );
+        assert_eq!(
+            contract.claim_reward(String::from("Doll")),
+            Err(Error::BudgetNotClaimedYet)
+        );
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(
+            contract.claim_reward(String::from("Doll")),
+            Err(Error::BudgetNotClaimedYet)
+        );
+    }
+
+    #[ink::test]
+    fn test_reward_refunded_donor() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    milestones: milestones(&[50, 50]).milestones,
+                    reward_tiers: tiers(&[(10, 2)]),
+                    reward_collection: Some(AccountId::from([0x07; 32])),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+
+        advance_to(5);
+        contract.make_vote(String::from("Doll"), true).ok();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+
+        // the second milestone fails and the donor takes back the rest
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract.start_milestone_vote(String::from("Doll")).ok();
+        contract
+            .make_milestone_vote(String::from("Doll"), false)
+            .ok();
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.claim_reward(String::from("Doll")),
+            Err(Error::NoReward)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.claim_reward(String::from("Doll")),
+            Err(Error::NoReward)
+        );
+    }
+
+    #[ink::test]
+    fn test_cancel_project() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(150);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::YouAreNotTheFather)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::ProjectCancelled)
+        );
+        assert_eq!(
+            contract.get_project_status(String::from("Doll")),
+            Ok(ProjectStatus::Cancelled)
+        );
+
+        // no more donations, refunds before the deadline
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::ProjectCancelled)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::NoFundsToRefund)
+        );
+
+        // the goal was reached, but there is no voting nor claim
+        advance_to(5);
+        assert_eq!(
+            contract.make_vote(String::from("Doll"), true),
+            Err(Error::ProjectCancelled)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::ProjectCancelled)
+        );
+    }
+
+    #[ink::test]
+    fn test_refund_large_donation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        // the pro-rata refund doesn't overflow for amounts of 18-decimal tokens
+        let amount = 20_000_000_000_000_000_000;
+        let contract_account = test::callee::<DefaultEnvironment>();
+        test::set_account_balance::<DefaultEnvironment>(contract_account, amount);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(amount);
+        contract.make_donation(String::from("Doll")).ok();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        assert_eq!(
+            test::get_account_balance::<DefaultEnvironment>(contract_account),
+            Ok(0)
+        );
+    }
+
+    #[ink::test]
+    fn test_vote_large_budget() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    voting_rules: Some(VotingRules {
+                        threshold: 66,
+                        quorum: 70,
+                        default_outcome: false,
+                    }),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        // percentages of the budget don't overflow
+        for (donor, amount) in [(accs.bob, u128::MAX / 3), (accs.charlie, u128::MAX / 3)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        test::set_value_transferred::<DefaultEnvironment>(u128::MAX / 4);
+        contract.make_donation(String::from("Doll")).ok();
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Err(Error::CampaignResultUnknown)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Ok(true)
+        );
+    }
+
+    #[ink::test]
+    fn test_cancel_after_deadline() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        advance_to(5);
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::DeadlinePassed)
+        );
+    }
+
+    #[ink::test]
+    fn test_project_status() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        for name in ["Doll", "Toy car", "Kite"].iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::default(),
+                )
+                .ok();
+        }
+        assert_eq!(
+            contract.get_project_status(String::from("Doll")),
+            Ok(ProjectStatus::Active)
+        );
+        assert_eq!(
+            contract.get_project_status(String::from("Teddy bear")),
+            Err(Error::ProjectDoesntExist)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+        contract.make_donation(String::from("Toy car")).ok();
+
+        advance_to(5);
+        assert_eq!(
+            contract.get_project_status(String::from("Doll")),
+            Ok(ProjectStatus::Voting)
+        );
+        assert_eq!(
+            contract.get_project_status(String::from("Kite")),
+            Ok(ProjectStatus::Failed)
+        );
+
+        contract.make_vote(String::from("Doll"), true).ok();
+        contract.make_vote(String::from("Toy car"), false).ok();
+        assert_eq!(
+            contract.get_project_status(String::from("Doll")),
+            Ok(ProjectStatus::Succeeded)
+        );
+        assert_eq!(
+            contract.get_project_status(String::from("Toy car")),
+            Ok(ProjectStatus::Failed)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.claim_budget(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_project_status(String::from("Doll")),
+            Ok(ProjectStatus::Claimed)
+        );
+    }
+
+    #[ink::test]
+    fn test_withdraw_donation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                30,
+                100,
+                Box::new(ProjectOptions {
+                    withdrawal_lock_in: Some(12),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(150);
+        contract.make_donation(String::from("Doll")).ok();
+
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 0),
+            Err(Error::IncorrectWithdrawalAmount)
+        );
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 151),
+            Err(Error::IncorrectWithdrawalAmount)
+        );
+        assert_eq!(contract.withdraw_donation(String::from("Doll"), 50), Ok(()));
+        assert_eq!(
+            contract.get_donated_amount(String::from("Doll"), accs.bob),
+            Ok(100)
+        );
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(100));
+        assert_eq!(
+            contract.get_donor_withdrawn(String::from("Doll"), accs.bob),
+            Ok(50)
+        );
+
+        // withdrawing everything and donating again doesn't duplicate the index entry
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 100),
+            Ok(())
+        );
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 1),
+            Err(Error::NoFundsToRefund)
+        );
+        test::set_value_transferred::<DefaultEnvironment>(120);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(contract.get_donation_count(accs.bob), Ok(1));
+
+        // the last 12 units before the deadline are locked in
+        advance_to(18);
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 20),
+            Err(Error::WithdrawalsLocked)
+        );
+        advance_to(30);
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 20),
+            Err(Error::DeadlinePassed)
+        );
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(120));
+
+        let events = recorded_events();
+        match &events[2] {
+            Event::DonationWithdrawn(event) => assert_eq!(
+                event,
+                &DonationWithdrawn {
+                    project_name: String::from("Doll"),
+                    donor: accs.bob,
+                    amount: 50,
+                }
+            ),
+            _ => panic!("expected DonationWithdrawn event"),
+        }
+    }
+
+    #[ink::test]
+    fn test_withdrawals_not_allowed() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                30,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(150);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.withdraw_donation(String::from("Doll"), 50),
+            Err(Error::WithdrawalsNotAllowed)
+        );
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(150));
+    }
+
+    #[ink::test]
+    fn test_extend_deadline_grace_period() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                30,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 10),
+            Err(Error::YouAreNotTheFather)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 0),
+            Err(Error::IncorrectExtension)
+        );
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 31 * 24 * 60 * 60 * 1000),
+            Err(Error::IncorrectExtension)
+        );
+        assert_eq!(contract.extend_deadline(String::from("Doll"), 10), Ok(()));
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 10),
+            Err(Error::DeadlineAlreadyExtended)
+        );
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            40
+        );
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), true),
+            Err(Error::NoSuchVote)
+        );
+
+        match &recorded_events()[1] {
+            Event::DeadlineExtended(event) => assert_eq!(
+                event,
+                &DeadlineExtended {
+                    project_name: String::from("Doll"),
+                    deadline: 40,
+                }
+            ),
+            _ => panic!("expected DeadlineExtended event"),
+        }
+
+        advance_to(40);
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 10),
+            Err(Error::DeadlinePassed)
+        );
+    }
+
+    #[ink::test]
+    fn test_extend_deadline_approval() {
+        let day = 24 * 60 * 60 * 1000;
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                30,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        for (donor, amount) in [(accs.bob, 40), (accs.charlie, 30), (accs.django, 20)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 2 * day),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_extension_proposal(String::from("Doll")),
+            Ok(2 * day)
+        );
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            30
+        );
+
+        // outsiders can't vote, objectors can refund right away
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), true),
+            Err(Error::NoFundsDontatedNoVote)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::DeadlineNotPassedYet)
+        );
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), false),
+            Err(Error::AlreadyVoted)
+        );
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(50));
+        assert_eq!(
+            contract.get_extension_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 0,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            })
+        );
+
+        // 30 out of the remaining 50 is a majority
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), true),
+            Ok(())
+        );
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            30 + 2 * day
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 10),
+            Err(Error::DeadlineAlreadyExtended)
+        );
+
+        // donations go on after the original deadline
+        advance_to(30);
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_extend_deadline_not_approved() {
+        let day = 24 * 60 * 60 * 1000;
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                30,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        for donor in [accs.bob, accs.charlie].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(50);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.extend_deadline(String::from("Doll"), 2 * day).ok();
+
+        // half of the budget is not a majority
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract
+            .make_extension_vote(String::from("Doll"), true)
+            .ok();
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            30
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.pause(false).ok();
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), true),
+            Err(Error::ContractPaused)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.unpause().ok();
+        contract.cancel_project(String::from("Doll")).ok();
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), true),
+            Err(Error::ProjectCancelled)
+        );
+
+        advance_to(30);
+        assert_eq!(
+            contract.make_extension_vote(String::from("Doll"), true),
+            Err(Error::DeadlinePassed)
+        );
+    }
+
+    #[ink::test]
+    fn test_voting_bounds() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        let bounds = VotingBounds {
+            min_threshold: 60,
+            max_threshold: 75,
+            min_quorum: 10,
+            max_quorum: 50,
+            default_success_allowed: false,
+        };
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.set_voting_bounds(bounds.clone()),
+            Err(Error::NotOwner)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        for (min_threshold, max_threshold, min_quorum, max_quorum) in [
+            (40, 75, 10, 50),
+            (80, 75, 10, 50),
+            (60, 100, 10, 50),
+            (60, 75, 60, 50),
+            (60, 75, 10, 101),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                contract.set_voting_bounds(VotingBounds {
+                    min_threshold: *min_threshold,
+                    max_threshold: *max_threshold,
+                    min_quorum: *min_quorum,
+                    max_quorum: *max_quorum,
+                    default_success_allowed: false,
+                }),
+                Err(Error::IncorrectVotingRules)
+            );
+        }
+        assert_eq!(contract.set_voting_bounds(bounds.clone()), Ok(()));
+        assert_eq!(contract.get_voting_bounds(), Ok(bounds));
+
+        for (threshold, quorum, default_outcome) in [
+            (55, 20, false),
+            (80, 20, false),
+            (66, 5, false),
+            (66, 60, false),
+            (66, 20, true),
+        ] {
+            assert_eq!(
+                contract.create_project(
+                    String::from("Doll"),
+                    String::from("I want a doll."),
+                    5,
+                    100,
+                    Box::new(ProjectOptions {
+                        voting_rules: Some(VotingRules {
+                            threshold,
+                            quorum,
+                            default_outcome,
+                        }),
+                        ..Default::default()
+                    })
+                ),
+                Err(Error::IncorrectVotingRules)
+            );
+        }
+
+        // the defaults follow the bounds
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .voting_rules,
+            VotingRules {
+                threshold: 60,
+                quorum: 10,
+                default_outcome: false,
+            }
+        );
+    }
+
+    macro_rules! voting_rules_tests {
+        ($($name:ident: $rules:expr, $votes:expr, $closed:expr, $result:expr,)*) => {
+        $(
+            #[ink::test]
+            fn $name() {
+                let accs = test::default_accounts::<DefaultEnvironment>();
+                test::set_caller::<DefaultEnvironment>(accs.alice);
+                let mut contract = Crowdfund::new(3, 0, accs.alice);
+                let (threshold, quorum, default_outcome) = $rules;
+                contract.create_project(String::from("Doll"), String::from("I want a doll."), 5, 100, Box::new(ProjectOptions { voting_rules: Some(VotingRules { threshold, quorum, default_outcome }), ..Default::default() })).ok();
+
+                // budget of 100 split 40 / 30 / 20 / 10
+                let donors = [(accs.bob, 40), (accs.charlie, 30), (accs.django, 20), (accs.eve, 10)];
+                for (donor, amount) in donors.iter() {
+                    test::set_caller::<DefaultEnvironment>(*donor);
+                    test::set_value_transferred::<DefaultEnvironment>(*amount);
+                    contract.make_donation(String::from("Doll")).ok();
+                }
+
+                advance_to(5);
+                let votes: &[(usize, bool)] = &$votes;
+                for (donor, vote) in votes.iter() {
+                    test::set_caller::<DefaultEnvironment>(donors[*donor].0);
+                    assert_eq!(contract.make_vote(String::from("Doll"), *vote), Ok(()));
+                }
+
+                if $closed {
+                    advance_to(9);
+                }
+                assert_eq!(contract.get_project_voting_result(String::from("Doll")), $result);
+            }
+        )*
+        }
+    }
+
+    voting_rules_tests! {
+        majority_reached: (50, 0, false), [(0, true), (2, true)], false, Ok(true),
+        majority_undecided: (50, 0, false), [(0, true), (1, false)], false, Err(Error::CampaignResultUnknown),
+        majority_missed_closed: (50, 0, false), [(0, true), (1, false)], true, Ok(false),
+        majority_rejected: (50, 0, false), [(0, false), (3, false)], false, Ok(false),
+        supermajority_reached: (66, 0, false), [(0, true), (1, true)], false, Ok(true),
+        supermajority_undecided: (66, 0, false), [(0, true), (2, true)], false, Err(Error::CampaignResultUnknown),
+        supermajority_missed_closed: (66, 0, false), [(0, true), (2, true)], true, Ok(false),
+        supermajority_rejected: (66, 0, false), [(0, false)], false, Ok(false),
+        quorum_pending_default_failure: (50, 80, false), [(0, true), (1, true)], false, Err(Error::CampaignResultUnknown),
+        quorum_missed_default_failure: (50, 80, false), [(0, true), (1, true)], true, Ok(false),
+        quorum_met_default_failure: (50, 80, false), [(0, true), (1, true), (3, false)], false, Ok(true),
+        quorum_pending_default_success: (50, 80, true), [(0, true), (1, true)], false, Ok(true),
+        quorum_missed_default_success: (50, 80, true), [], true, Ok(true),
+        quorum_missed_against_default_success: (50, 60, true), [(0, false), (3, false)], false, Err(Error::CampaignResultUnknown),
+        quorum_missed_against_closed_default_success: (50, 60, true), [(0, false), (3, false)], true, Ok(true),
+        quorum_met_against_default_success: (50, 60, true), [(0, false), (1, false)], false, Ok(false),
+        quorum_met_closed_default_success: (50, 60, true), [(0, true), (1, false)], true, Ok(false),
+    }
+
+    #[ink::test]
+    fn test_change_vote() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        for (donor, amount) in [(accs.bob, 40), (accs.charlie, 40), (accs.django, 20)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.withdraw_vote(String::from("Doll")),
+            Err(Error::NoSuchVote)
+        );
+        assert_eq!(contract.make_vote(String::from("Doll"), false), Ok(()));
+        assert_eq!(
+            contract.make_vote(String::from("Doll"), false),
+            Err(Error::AlreadyVoted)
+        );
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        assert_eq!(
+            contract.get_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 40,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            })
+        );
+
+        assert_eq!(contract.withdraw_vote(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_vote(String::from("Doll"), accs.bob),
+            Err(Error::NoSuchVote)
+        );
+        assert_eq!(
+            contract.get_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 0,
+                ovr_voted_no: 0,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            })
+        );
+
+        let changes: Vec<Option<bool>> = recorded_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::VoteChanged(event) => {
+                    assert_eq!(event.weight, 40);
+                    Some(event.vote)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(changes, vec![Some(true), None]);
+
+        // a result decided early can still be changed, until the budget is claimed
+        contract.make_vote(String::from("Doll"), true).ok();
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Ok(true)
+        );
+        assert_eq!(contract.make_vote(String::from("Doll"), false), Ok(()));
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Err(Error::CampaignResultUnknown)
+        );
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_vote(String::from("Doll"), false),
+            Err(Error::VotingAlreadyDecided)
+        );
+        assert_eq!(
+            contract.withdraw_vote(String::from("Doll")),
+            Err(Error::VotingAlreadyDecided)
+        );
+
+        advance_to(9);
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(
+            contract.withdraw_vote(String::from("Doll")),
+            Err(Error::VotingDeadlinePassed)
+        );
+    }
+
+    #[ink::test]
+    fn test_change_vote_after_refund() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        for (donor, amount) in [(accs.bob, 40), (accs.charlie, 40), (accs.django, 20)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+
+        advance_to(5);
+        for donor in [accs.bob, accs.charlie].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            contract.make_vote(String::from("Doll"), false).ok();
+        }
+
+        // once a donor got refunded the votes stay as they are
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_vote(String::from("Doll"), true),
+            Err(Error::VotingAlreadyDecided)
+        );
+        assert_eq!(
+            contract.withdraw_vote(String::from("Doll")),
+            Err(Error::VotingAlreadyDecided)
+        );
+    }
+
+    fn delegation_project(
+        contract: &mut Crowdfund,
+        accs: &test::DefaultAccounts<DefaultEnvironment>,
+        options: ProjectOptions,
+    ) {
+        // budget of 100 split 40 / 30 / 20 / 10 between bob, charlie, django and eve
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(options),
+            )
+            .ok();
+
+        for (donor, amount) in [
+            (accs.bob, 40),
+            (accs.charlie, 30),
+            (accs.django, 20),
+            (accs.eve, 10),
+        ]
+        .iter()
+        {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+    }
+
+    #[ink::test]
+    fn test_delegate_vote() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        delegation_project(&mut contract, &accs, ProjectOptions::default());
+
+        // accounts without donations have nothing to delegate
+        test::set_caller::<DefaultEnvironment>(accs.frank);
+        assert_eq!(
+            contract.delegate_vote(Some(String::from("Doll")), accs.bob),
+            Err(Error::NoFundsDontatedNoVote)
+        );
+        assert_eq!(
+            contract.delegate_vote(None, accs.bob),
+            Err(Error::NoFundsDontatedNoVote)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(
+            contract.delegate_vote(Some(String::from("Doll")), accs.django),
+            Err(Error::IncorrectDelegate)
+        );
+        assert_eq!(
+            contract.delegate_vote(Some(String::from("Kite")), accs.bob),
+            Err(Error::ProjectDoesntExist)
+        );
+        assert_eq!(contract.revoke_delegation(None), Err(Error::NoDelegation));
+        assert_eq!(
+            contract.delegate_vote(Some(String::from("Doll")), accs.bob),
+            Ok(())
+        );
+
+        // general delegation is overridden by the project one
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        contract.delegate_vote(None, accs.charlie).ok();
+        contract
+            .delegate_vote(Some(String::from("Doll")), accs.bob)
+            .ok();
+        assert_eq!(
+            contract.get_delegators(Some(String::from("Doll")), accs.bob, 0, 10),
+            Ok(vec![accs.django, accs.eve])
+        );
+        assert_eq!(
+            contract.get_delegate(None, accs.eve),
+            Ok(Some(accs.charlie))
+        );
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        assert_eq!(
+            contract.get_vote_weight(String::from("Doll"), accs.bob),
+            Ok(70)
+        );
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Ok(true)
+        );
+
+        // eve's weight was already used by bob, not by charlie
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.make_vote(String::from("Doll"), false), Ok(()));
+        assert_eq!(
+            contract.get_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 70,
+                ovr_voted_no: 30,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            })
+        );
+
+        // the claimed result can't be turned by a direct vote
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(
+            contract.make_vote(String::from("Doll"), false),
+            Err(Error::VotingAlreadyDecided)
+        );
+    }
+
+    #[ink::test]
+    fn test_delegation_overridden_by_direct_vote() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        delegation_project(
+            &mut contract,
+            &accs,
+            ProjectOptions {
+                voting_rules: Some(VotingRules {
+                    threshold: 66,
+                    quorum: 0,
+                    default_outcome: false,
+                }),
+                ..Default::default()
+            },
+        );
+
+        // bob delegates to django, who delegates to charlie: not transitive
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract.delegate_vote(None, accs.django).ok();
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        contract.delegate_vote(None, accs.charlie).ok();
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        contract.delegate_vote(None, accs.charlie).ok();
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.get_vote_weight(String::from("Doll"), accs.charlie),
+            Ok(60)
+        );
+
+        // eve takes the weight back
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        assert_eq!(contract.make_vote(String::from("Doll"), false), Ok(()));
+        assert_eq!(
+            contract.get_vote_weight(String::from("Doll"), accs.charlie),
+            Ok(50)
+        );
+        assert_eq!(
+            contract.get_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 50,
+                ovr_voted_no: 10,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            })
+        );
+
+        // django takes the weight back and votes with bob's
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        assert_eq!(
+            contract.get_vote_weight(String::from("Doll"), accs.django),
+            Ok(60)
+        );
+        assert_eq!(
+            contract.get_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 90,
+                ovr_voted_no: 10,
+                quad_voted_yes: 0,
+                quad_voted_no: 0,
+            })
+        );
+    }
+
+    #[ink::test]
+    fn test_delegate_again() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        delegation_project(&mut contract, &accs, ProjectOptions::default());
+
+        // a full delegate still takes the donors who delegate to them again
+        let donors: Vec<AccountId> = (0..50)
+            .map(|index| AccountId::from([0x80 + index; 32]))
+            .collect();
+        for donor in donors.iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(1);
+            contract.make_donation(String::from("Doll")).ok();
+            contract
+                .delegate_vote(Some(String::from("Doll")), accs.bob)
+                .ok();
+        }
+        assert_eq!(
+            contract.delegate_vote(Some(String::from("Doll")), accs.bob),
+            Ok(())
+        );
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(
+            contract.delegate_vote(Some(String::from("Doll")), accs.bob),
+            Err(Error::TooManyDelegators)
+        );
+
+        // the weight of a withdrawn vote goes to the next delegate
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        contract
+            .delegate_vote(Some(String::from("Doll")), accs.eve)
+            .ok();
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.eve);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(contract.withdraw_vote(String::from("Doll")), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        contract
+            .delegate_vote(Some(String::from("Doll")), accs.charlie)
+            .ok();
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        assert_eq!(
+            contract.get_vote_weight(String::from("Doll"), accs.charlie),
+            Ok(50)
+        );
+    }
+
+    #[ink::test]
+    fn test_revoke_delegation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        delegation_project(&mut contract, &accs, ProjectOptions::default());
+
+        for donor in [accs.charlie, accs.django, accs.eve].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            contract.delegate_vote(None, accs.frank).ok();
+        }
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.revoke_delegation(None), Ok(()));
+        assert_eq!(
+            contract.get_delegators(None, accs.frank, 0, 10),
+            Ok(vec![accs.eve, accs.django])
+        );
+
+        // frank didn't donate, but votes with the delegated weight
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.frank);
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        assert_eq!(
+            contract.get_vote_weight(String::from("Doll"), accs.frank),
+            Ok(30)
+        );
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for (value, root) in [
+            (0, 0),
+            (1, 1),
+            (3, 1),
+            (4, 2),
+            (99, 9),
+            (100, 10),
+            (1 << 64, 1 << 32),
+        ]
+        .iter()
+        {
+            assert_eq!(isqrt(*value), *root);
+        }
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_mul_div() {
+        for (value, numerator, denominator, result) in [
+            (0, 5, 7, 0),
+            (100, 1, 3, 33),
+            (100, 2, 3, 66),
+            (u128::MAX, 1, 1, u128::MAX),
+            (u128::MAX, 3, 7, u128::MAX / 7 * 3 + 1),
+            (u128::MAX, u128::MAX - 1, u128::MAX, u128::MAX - 1),
+            (
+                20_000_000_000_000_000_000,
+                10,
+                20_000_000_000_000_000_000,
+                10,
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(mul_div(*value, *numerator, *denominator), *result);
+        }
+        assert_eq!(mul_div(u128::MAX, 2, 1), u128::MAX);
+    }
+
+    #[ink::test]
+    fn test_quadratic_voting() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    quadratic_voting: true,
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        // the square root is taken of the whole donation
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        for amount in [36, 64].iter() {
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+        for (donor, amount) in [(accs.charlie, 9), (accs.django, 16), (accs.eve, 25)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+        assert_eq!(contract.get_quadratic_budget(String::from("Doll")), Ok(22));
+
+        // the whale alone doesn't decide
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Err(Error::CampaignResultUnknown)
+        );
+
+        for donor in [accs.django, accs.eve].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            contract.make_vote(String::from("Doll"), false).ok();
+        }
+        assert_eq!(
+            contract.get_voting_state(String::from("Doll")),
+            Ok(ProjectVotes {
+                ovr_voted_yes: 100,
+                ovr_voted_no: 41,
+                quad_voted_yes: 10,
+                quad_voted_no: 9,
+            })
+        );
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Err(Error::CampaignResultUnknown)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        contract.make_vote(String::from("Doll"), false).ok();
+        assert_eq!(
+            contract.get_project_voting_result(String::from("Doll")),
+            Ok(false)
+        );
+    }
+
+    fn matching_round(contract: &mut Crowdfund, accs: &test::DefaultAccounts<DefaultEnvironment>) {
+        // Doll gets 4 from each of four donors, Kite 9 from each of two
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        for name in ["Doll", "Kite"].iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    12,
+                    10,
+                    Box::default(),
+                )
+                .ok();
+        }
+        contract
+            .create_round(
+                0,
+                10,
+                None,
+                vec![String::from("Doll"), String::from("Kite")],
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.frank);
+        test::set_value_transferred::<DefaultEnvironment>(660);
+        contract.fund_round(0).ok();
+
+        for donor in [accs.bob, accs.charlie, accs.django, accs.eve].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(4);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+        for donor in [accs.bob, accs.charlie].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(9);
+            contract.make_donation(String::from("Kite")).ok();
+        }
+    }
+
+    #[ink::test]
+    fn test_round_validation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        matching_round(&mut contract, &accs);
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.create_round(0, 10, None, vec![String::from("Doll")]),
+            Err(Error::NotOwner)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                12,
+                10,
+                Box::default(),
+            )
+            .ok();
+        assert_eq!(
+            contract.create_round(10, 10, None, vec![String::from("Toy car")]),
+            Err(Error::IncorrectRound)
+        );
+        assert_eq!(
+            contract.create_round(0, 10, None, vec![]),
+            Err(Error::IncorrectRound)
+        );
+        assert_eq!(
+            contract.create_round(0, 13, None, vec![String::from("Toy car")]),
+            Err(Error::IncorrectRound)
+        );
+        assert_eq!(
+            contract.create_round(0, 10, None, vec![String::from("Teddy bear")]),
+            Err(Error::ProjectDoesntExist)
+        );
+        assert_eq!(
+            contract.create_round(
+                0,
+                10,
+                Some(AccountId::from([0x07; 32])),
+                vec![String::from("Toy car")]
+            ),
+            Err(Error::WrongCurrency)
+        );
+        assert_eq!(
+            contract.create_round(
+                0,
+                10,
+                None,
+                vec![String::from("Toy car"), String::from("Doll")]
+            ),
+            Err(Error::ProjectAlreadyInRound)
+        );
+        assert_eq!(
+            contract.create_round(
+                0,
+                10,
+                None,
+                vec![String::from("Toy car"), String::from("Toy car")]
+            ),
+            Err(Error::ProjectAlreadyInRound)
+        );
+        assert_eq!(
+            contract.get_project_round(String::from("Toy car")),
+            Ok(None)
+        );
+        assert_eq!(contract.get_round_count(), Ok(1));
+
+        // no early finalisation, no late funding
+        assert_eq!(contract.finalise_round(0, 10), Err(Error::RoundNotEnded));
+        assert_eq!(
+            contract.get_matched_amount(String::from("Doll")),
+            Err(Error::RoundNotFinalised)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.pause(false).ok();
+        test::set_caller::<DefaultEnvironment>(accs.frank);
+        assert_eq!(contract.fund_round(0), Err(Error::ContractPaused));
+        assert_eq!(
+            contract.fund_round_tokens(0, 10),
+            Err(Error::ContractPaused)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.unpause().ok();
+        advance_to(10);
+        test::set_caller::<DefaultEnvironment>(accs.frank);
+        assert_eq!(contract.fund_round(0), Err(Error::RoundEnded));
+        assert_eq!(contract.fund_round(1), Err(Error::NoSuchRound));
+
+        // cancelled projects and those past their deadline can't be matched anymore
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract
+            .create_project(
+                String::from("Ball"),
+                String::from("I want a ball."),
+                20,
+                10,
+                Box::default(),
+            )
+            .ok();
+        contract.cancel_project(String::from("Ball")).ok();
+        assert_eq!(
+            contract.create_round(10, 20, None, vec![String::from("Ball")]),
+            Err(Error::ProjectCancelled)
+        );
+        advance_to(12);
+        assert_eq!(
+            contract.create_round(12, 20, None, vec![String::from("Toy car")]),
+            Err(Error::DeadlinePassed)
+        );
+    }
+
+    #[ink::test]
+    fn test_round_matching() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        matching_round(&mut contract, &accs);
+        assert_eq!(contract.get_round_pool(0), Ok(660));
+        assert_eq!(contract.get_matching_donors(String::from("Doll")), Ok(4));
+
+        // donations after the round aren't matched
+        advance_to(10);
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Kite")).ok();
+        assert_eq!(contract.get_matching_donors(String::from("Kite")), Ok(2));
+
+        // scores: (4 * 2)^2 - 16 = 48 and (2 * 3)^2 - 18 = 18
+        for _ in 0..4 {
+            assert_eq!(contract.get_round_finalised(0), Ok(false));
+            assert_eq!(contract.finalise_round(0, 1), Ok(()));
+        }
+        assert_eq!(contract.get_round_finalised(0), Ok(true));
+        assert_eq!(contract.finalise_round(0, 1), Err(Error::RoundFinalised));
+        assert_eq!(contract.get_matched_amount(String::from("Doll")), Ok(480));
+        assert_eq!(contract.get_matched_amount(String::from("Kite")), Ok(180));
+        assert_eq!(contract.get_round_pool(0), Ok(0));
+
+        advance_to(12);
+        for donor in [accs.bob, accs.charlie].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            contract.make_vote(String::from("Doll"), true).ok();
+            contract.make_vote(String::from("Kite"), false).ok();
+        }
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        contract.make_vote(String::from("Doll"), true).ok();
+        contract.make_vote(String::from("Kite"), false).ok();
+
+        // the match comes with the budget
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::BudgetClaimed(event)) => assert_eq!(event.amount, 16 + 480),
+            _ => panic!("expected BudgetClaimed event"),
+        }
+        assert_eq!(
+            contract.return_matching(String::from("Doll")),
+            Err(Error::CampaignSuccessfulNoRefunds)
+        );
+
+        // the failed project returns its match to the pool
+        assert_eq!(contract.return_matching(String::from("Kite")), Ok(()));
+        assert_eq!(
+            contract.return_matching(String::from("Kite")),
+            Err(Error::NoMatchedFunds)
+        );
+        assert_eq!(contract.get_round_pool(0), Ok(180));
+        match recorded_events().last() {
+            Some(Event::MatchingReturned(event)) => assert_eq!(
+                event,
+                &MatchingReturned {
+                    project_name: String::from("Kite"),
+                    round: 0,
+                    amount: 180,
+                }
+            ),
+            _ => panic!("expected MatchingReturned event"),
+        }
+
+        // and the owner moves it on to the next round
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                30,
+                10,
+                Box::default(),
+            )
+            .ok();
+        contract
+            .create_round(12, 30, None, vec![String::from("Toy car")])
+            .ok();
+        assert_eq!(contract.rollover_pool(0, 1), Ok(()));
+        assert_eq!(contract.get_round_pool(0), Ok(0));
+        assert_eq!(contract.get_round_pool(1), Ok(180));
+    }
+
+    fn claimed_and_fee() -> (u128, u128) {
+        let mut claimed = 0;
+        let mut fee = 0;
+        for event in recorded_events() {
+            match event {
+                Event::BudgetClaimed(event) => claimed += event.amount,
+                Event::FeePaid(event) => fee += event.amount,
+                _ => (),
+            }
+        }
+        (claimed, fee)
+    }
+
+    #[ink::test]
+    fn test_flexible_funding() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 10, accs.alice);
+        for (name, mode) in [
+            ("Doll", FundingMode::AllOrNothing),
+            ("Kite", FundingMode::Flexible),
+        ] {
+            contract
+                .create_project(
+                    String::from(name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::new(ProjectOptions {
+                        funding_mode: mode,
+                        ..Default::default()
+                    }),
+                )
+                .ok();
+        }
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Kite"))
+                .unwrap()
+                .funding_mode,
+            FundingMode::Flexible
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(60);
+        contract.make_donation(String::from("Doll")).ok();
+        contract.make_donation(String::from("Kite")).ok();
+
+        advance_to(5);
+        assert_eq!(
+            contract.make_vote(String::from("Kite"), true),
+            Err(Error::VotingNotRequired)
+        );
+        assert_eq!(
+            contract.get_project_status(String::from("Doll")),
+            Ok(ProjectStatus::Failed)
+        );
+        assert_eq!(
+            contract.get_project_status(String::from("Kite")),
+            Ok(ProjectStatus::Succeeded)
+        );
+        assert_eq!(
+            contract.refund_donation(String::from("Kite")),
+            Err(Error::CampaignSuccessfulNoRefunds)
+        );
+        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::GoalNotReached)
+        );
+        assert_eq!(contract.claim_budget(String::from("Kite")), Ok(()));
+        assert_eq!(claimed_and_fee(), (54, 6));
+    }
+
+    #[ink::test]
+    fn test_flexible_funding_with_vote() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 10, accs.alice);
+        for name in ["Doll", "Kite"].iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::new(ProjectOptions {
+                        funding_mode: FundingMode::FlexibleWithVote,
+                        ..Default::default()
+                    }),
+                )
+                .ok();
+        }
+        contract
+            .create_project(
+                String::from("Toy car"),
+                String::from("I want a toy car."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        contract.make_donation(String::from("Doll")).ok();
+        contract.make_donation(String::from("Kite")).ok();
+        test::set_value_transferred::<DefaultEnvironment>(200);
+        contract.make_donation(String::from("Toy car")).ok();
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::CampaignResultUnknown)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+        assert_eq!(contract.make_vote(String::from("Kite"), false), Ok(()));
+        assert_eq!(contract.make_vote(String::from("Toy car"), true), Ok(()));
+        assert_eq!(contract.refund_donation(String::from("Kite")), Ok(()));
+        assert_eq!(
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::CampaignSuccessfulNoRefunds)
+        );
+
+        // the fee is taken the same way in both modes
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        assert_eq!(contract.claim_budget(String::from("Toy car")), Ok(()));
+        assert_eq!(
+            contract.claim_budget(String::from("Kite")),
+            Err(Error::CampaignUnsuccessfulNoClaims)
+        );
+        assert_eq!(claimed_and_fee(), (45 + 180, 5 + 20));
+    }
+
+    fn goals(amounts: &[u128]) -> Vec<StretchGoal> {
+        amounts
+            .iter()
+            .map(|amount| StretchGoal {
+                amount: *amount,
+                description: String::from("More dolls."),
+            })
+            .collect()
+    }
+
+    #[ink::test]
+    fn test_stretch_goals() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        for amounts in [&[100u128][..], &[150, 150], &[200, 150], &[150; 11]].iter() {
+            assert_eq!(
+                contract.create_project(
+                    String::from("Doll"),
+                    String::from("I want a doll."),
+                    5,
+                    100,
+                    Box::new(ProjectOptions {
+                        stretch_goals: goals(amounts),
+                        ..Default::default()
+                    })
+                ),
+                Err(Error::IncorrectStretchGoals)
+            );
+        }
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    stretch_goals: goals(&[150, 200]),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(149);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_unlocked_stretch_goals(String::from("Doll")),
+            Ok(0)
+        );
+        test::set_value_transferred::<DefaultEnvironment>(1);
+        contract.make_donation(String::from("Doll")).ok();
+        assert_eq!(
+            contract.get_unlocked_stretch_goals(String::from("Doll")),
+            Ok(1)
+        );
+
+        // without the votes the whole budget is claimable
+        advance_to(5);
+        assert_eq!(
+            contract.make_stretch_vote(String::from("Doll"), 0, false),
+            Err(Error::VotingNotRequired)
+        );
+        assert_eq!(contract.get_claimable_budget(String::from("Doll")), Ok(150));
+    }
+
+    #[ink::test]
+    fn test_stretch_goal_rejected() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    stretch_goals: goals(&[150, 200, 300]),
+                    stretch_goal_votes: true,
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        for (donor, amount) in [(accs.bob, 150), (accs.charlie, 100)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+
+        advance_to(5);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.make_stretch_vote(String::from("Doll"), 2, true),
+            Err(Error::NoSuchStretchGoal)
+        );
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.make_stretch_vote(String::from("Doll"), 0, true),
+            Ok(())
+        );
+        assert_eq!(
+            contract.make_stretch_vote(String::from("Doll"), 0, true),
+            Err(Error::AlreadyVoted)
+        );
+        assert_eq!(
+            contract.get_claimable_budget(String::from("Doll")),
+            Err(Error::CampaignResultUnknown)
+        );
+        assert_eq!(
+            contract.make_stretch_vote(String::from("Doll"), 1, false),
+            Ok(())
+        );
+
+        // the second stretch goal was rejected, 50 out of 250 goes back
+        assert_eq!(contract.get_claimable_budget(String::from("Doll")), Ok(200));
+        assert_eq!(
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::CampaignSuccessfulNoRefunds)
+        );
+        assert_eq!(
+            contract.refund_stretch_portion(String::from("Doll")),
+            Ok(())
+        );
+        assert_eq!(
+            contract.refund_stretch_portion(String::from("Doll")),
+            Err(Error::NoFundsToRefund)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.refund_stretch_portion(String::from("Doll")),
+            Ok(())
+        );
+        let refunds: Vec<(AccountId, u128)> = recorded_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::RefundIssued(event) => Some((event.donor, event.amount)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(refunds, vec![(accs.bob, 30), (accs.charlie, 20)]);
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::BudgetClaimed(event)) => assert_eq!(event.amount, 200),
+            _ => panic!("expected BudgetClaimed event"),
+        }
+    }
+
+    #[ink::test]
+    fn test_max_budget_rejects_excess() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    max_budget: Some(99),
+                    ..Default::default()
+                })
+            ),
+            Err(Error::IncorrectMaxBudget)
+        );
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    max_budget: Some(150),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+        test::set_value_transferred::<DefaultEnvironment>(51);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::MaxBudgetExceeded)
+        );
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(100));
+
+        // hitting the cap exactly closes the campaign and opens the voting
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::MaxBudgetReached(event)) => assert_eq!(event.budget, 150),
+            _ => panic!("expected MaxBudgetReached event"),
+        }
+        let info = contract.get_project_info(String::from("Doll")).unwrap();
+        assert_eq!(info.deadline, 0);
+        test::set_value_transferred::<DefaultEnvironment>(1);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::DeadlinePassed)
+        );
+        assert_eq!(contract.make_vote(String::from("Doll"), true), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_max_budget_refunds_excess() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    max_budget: Some(150),
+                    refund_excess: true,
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(200);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(150));
+        assert_eq!(
+            contract.get_donated_amount(String::from("Doll"), accs.bob),
+            Ok(150)
+        );
+        let events = recorded_events();
+        match &events[events.len() - 2] {
+            Event::DonationMade(event) => assert_eq!(event.amount, 150),
+            _ => panic!("expected DonationMade event"),
+        }
+    }
+
+    #[ink::test]
+    fn test_donation_limits() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    min_donation: Some(20),
+                    max_donation_per_account: Some(10),
+                    ..Default::default()
+                })
+            ),
+            Err(Error::IncorrectDonationLimits)
+        );
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    min_donation: Some(10),
+                    max_donation_per_account: Some(50),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(9);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::DonationTooSmall)
+        );
+        test::set_value_transferred::<DefaultEnvironment>(10);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+
+        // once the minimum is met smaller top ups are fine, up to the maximum
+        test::set_value_transferred::<DefaultEnvironment>(1);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+        test::set_value_transferred::<DefaultEnvironment>(40);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::DonationTooLarge)
+        );
+        test::set_value_transferred::<DefaultEnvironment>(39);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_donated_amount(String::from("Doll"), accs.bob),
+            Ok(50)
+        );
+    }
+
+    #[ink::test]
+    fn test_zero_donation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::default(),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::DonationTooSmall)
+        );
+        assert_eq!(contract.get_donation_count(accs.bob), Ok(0));
+    }
+
+    #[ink::test]
+    fn test_private_project() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        for (name, private) in [("Doll", true), ("Car", false)].iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::new(ProjectOptions {
+                        private: *private,
+                        ..Default::default()
+                    }),
+                )
+                .ok();
+        }
+        assert_eq!(
+            contract.add_to_allowlist(String::from("Car"), accs.bob),
+            Err(Error::ProjectNotPrivate)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.add_to_allowlist(String::from("Doll"), accs.bob),
+            Err(Error::YouAreNotTheFather)
+        );
+        test::set_value_transferred::<DefaultEnvironment>(10);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::NotOnAllowlist)
+        );
+        assert_eq!(contract.make_donation(String::from("Car")), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.add_to_allowlist(String::from("Doll"), accs.bob),
+            Ok(())
+        );
+        match recorded_events().last() {
+            Some(Event::AllowlistChanged(event)) => {
+                assert_eq!(event.account, accs.bob);
+                assert!(event.allowed);
+            }
+            _ => panic!("expected AllowlistChanged event"),
+        }
+        assert_eq!(
+            contract.is_on_allowlist(String::from("Doll"), accs.bob),
+            Ok(true)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.remove_from_allowlist(String::from("Doll"), accs.bob),
+            Ok(())
+        );
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::NotOnAllowlist)
+        );
+        assert_eq!(
+            contract.get_donated_amount(String::from("Doll"), accs.bob),
+            Ok(10)
+        );
+    }
+
+    fn co_authors(accs: &test::DefaultAccounts<DefaultEnvironment>) -> Vec<Beneficiary> {
+        vec![
+            Beneficiary {
+                account: accs.alice,
+                share: 5_000,
+            },
+            Beneficiary {
+                account: accs.bob,
+                share: 3_333,
+            },
+            Beneficiary {
+                account: accs.charlie,
+                share: 1_667,
+            },
+        ]
+    }
+
+    #[ink::test]
+    fn test_beneficiaries_validation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        let mut beneficiaries = co_authors(&accs);
+        beneficiaries[0].share = 4_999;
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries,
+                    ..Default::default()
+                })
+            ),
+            Err(Error::IncorrectBeneficiaries)
+        );
+        let mut beneficiaries = co_authors(&accs);
+        beneficiaries[2].account = accs.bob;
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries,
+                    ..Default::default()
+                })
+            ),
+            Err(Error::IncorrectBeneficiaries)
+        );
+        assert_eq!(
+            contract.create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries: co_authors(&accs),
+                    approvals_required: 4,
+                    ..Default::default()
+                })
+            ),
+            Err(Error::IncorrectApprovals)
+        );
+    }
+
+    #[ink::test]
+    fn test_beneficiaries_claim() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries: co_authors(&accs),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        // co-authors can't donate to their own project
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::CantDonateOwnProject)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        contract.make_donation(String::from("Doll")).ok();
+        advance_to(5);
+        contract.make_vote(String::from("Doll"), true).ok();
+
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::YouAreNotTheFather)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        let claims: Vec<(AccountId, u128)> = recorded_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::BudgetClaimed(event) => Some((event.author, event.amount)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            claims,
+            vec![(accs.alice, 50), (accs.bob, 33), (accs.charlie, 17)]
+        );
+    }
+
+    #[ink::test]
+    fn test_co_author_approvals() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries: co_authors(&accs),
+                    approvals_required: 2,
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.django);
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::YouAreNotTheFather)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::AlreadyApproved)
+        );
+        assert_eq!(
+            contract.get_project_cancelled(String::from("Doll")),
+            Ok(false)
+        );
+        assert_eq!(
+            contract.get_action_approvals(String::from("Doll"), AuthorAction::CancelProject),
+            Ok(vec![accs.bob])
+        );
+
+        // approving a different extension doesn't count towards the first one
+        assert_eq!(contract.extend_deadline(String::from("Doll"), 1), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.extend_deadline(String::from("Doll"), 2), Ok(()));
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .deadline,
+            5
+        );
+
+        // enough co-authors approved, but the author didn't yet
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_project_cancelled(String::from("Doll")),
+            Ok(false)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_project_cancelled(String::from("Doll")),
+            Ok(true)
+        );
+        assert_eq!(
+            contract.get_action_approvals(String::from("Doll"), AuthorAction::CancelProject),
+            Ok(vec![])
+        );
+    }
+
+    #[ink::test]
+    fn test_co_author_without_approvals() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries: co_authors(&accs),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        // without extra approvals the author acts alone
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::YouAreNotTheFather)
+        );
+        assert_eq!(
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Err(Error::YouAreNotTheFather)
+        );
+        assert_eq!(contract.get_pending_author(String::from("Doll")), Ok(None));
+        assert_eq!(
+            contract.extend_deadline(String::from("Doll"), 1),
+            Err(Error::YouAreNotTheFather)
+        );
+        assert_eq!(
+            contract.get_project_cancelled(String::from("Doll")),
+            Ok(false)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_transfer_project() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        for name in ["Doll", "Car", "Ball"].iter() {
+            contract
+                .create_project(
+                    String::from(*name),
+                    String::from("I want a toy."),
+                    5,
+                    100,
+                    Box::default(),
+                )
+                .ok();
+        }
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Err(Error::YouAreNotTheFather)
+        );
+        test::set_value_transferred::<DefaultEnvironment>(10);
+        contract.make_donation(String::from("Doll")).ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_pending_author(String::from("Doll")),
+            Ok(Some(accs.bob))
+        );
+        assert_eq!(
+            contract.accept_project(String::from("Doll")),
+            Err(Error::NotPendingAuthor)
+        );
+
+        // donors have to take their donation back before they take over
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.accept_project(String::from("Doll")),
+            Err(Error::NewAuthorDonated)
+        );
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract
+            .transfer_project(String::from("Doll"), accs.charlie)
+            .ok();
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.accept_project(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::ProjectTransferred(event)) => {
+                assert_eq!(event.previous_author, accs.alice);
+                assert_eq!(event.author, accs.charlie);
             }
+            _ => panic!("expected ProjectTransferred event"),
         }
-    }
-}
-
-#[cfg(test)]
-mod tests {
+        assert_eq!(contract.get_pending_author(String::from("Doll")), Ok(None));
+        assert_eq!(
+            contract.get_projects_by_author(accs.alice, 0, 10),
+            Ok(vec![String::from("Ball"), String::from("Car")])
+        );
+        assert_eq!(
+            contract.get_projects_by_author(accs.charlie, 0, 10),
+            Ok(vec![String::from("Doll")])
+        );
 
-    use crate::crowdfund::Crowdfund;
-    use crate::crowdfund::Error;
-    use crate::crowdfund::ProjectInfo;
-    use crate::crowdfund::ProjectVotes;
+        // the new author can't donate, the previous one now can
+        test::set_value_transferred::<DefaultEnvironment>(90);
+        assert_eq!(
+            contract.make_donation(String::from("Doll")),
+            Err(Error::CantDonateOwnProject)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.make_donation(String::from("Doll")), Ok(()));
 
-    use ink_env::block_timestamp;
-    use ink_env::{test, DefaultEnvironment};
-    use ink_lang as ink;
+        advance_to(5);
+        contract.make_vote(String::from("Doll"), true).ok();
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract.make_vote(String::from("Doll"), true).ok();
+        assert_eq!(
+            contract.claim_budget(String::from("Doll")),
+            Err(Error::YouAreNotTheFather)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::BudgetClaimed(event)) => assert_eq!(event.author, accs.charlie),
+            _ => panic!("expected BudgetClaimed event"),
+        }
+    }
 
     #[ink::test]
-    fn test_create_project() {
+    fn test_transfer_project_moves_share() {
         let accs = test::default_accounts::<DefaultEnvironment>();
         test::set_caller::<DefaultEnvironment>(accs.alice);
         let mut contract = Crowdfund::new(3, 0, accs.alice);
-
         contract
-            .create_project(String::from("Doll"), String::from("I want a doll."), 5, 10)
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries: co_authors(&accs),
+                    ..Default::default()
+                }),
+            )
             .ok();
-        assert_eq!(contract.get_all_projects(), Ok(vec![String::from("Doll")]));
 
+        contract
+            .transfer_project(String::from("Doll"), accs.bob)
+            .ok();
         test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(contract.accept_project(String::from("Doll")), Ok(()));
+        let info = contract.get_project_info(String::from("Doll")).unwrap();
+        assert_eq!(info.author, accs.bob);
+        assert_eq!(
+            info.beneficiaries,
+            vec![
+                Beneficiary {
+                    account: accs.bob,
+                    share: 8_333,
+                },
+                Beneficiary {
+                    account: accs.charlie,
+                    share: 1_667,
+                },
+            ]
+        );
+    }
+
+    #[ink::test]
+    fn test_vesting_validation() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        for options in [
+            ProjectOptions {
+                vesting: Some(VestingSchedule {
+                    start: 4,
+                    cliff: 0,
+                    duration: 60,
+                }),
+                ..Default::default()
+            },
+            ProjectOptions {
+                vesting: Some(VestingSchedule {
+                    start: 10,
+                    cliff: 61,
+                    duration: 60,
+                }),
+                ..Default::default()
+            },
+            ProjectOptions {
+                vesting: Some(VestingSchedule {
+                    start: 10,
+                    cliff: 0,
+                    duration: 0,
+                }),
+                ..Default::default()
+            },
+            ProjectOptions {
+                milestones: milestones(&[50, 50]).milestones,
+                vesting: Some(VestingSchedule {
+                    start: 10,
+                    cliff: 0,
+                    duration: 60,
+                }),
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                contract.create_project(
+                    String::from("Doll"),
+                    String::from("I want a doll."),
+                    5,
+                    100,
+                    Box::new(options)
+                ),
+                Err(Error::IncorrectVesting)
+            );
+        }
+    }
+
+    #[ink::test]
+    fn test_vesting_release() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
         contract
             .create_project(
-                String::from("Toy car"),
-                String::from("I want a toy car."),
-                6,
-                12,
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    vesting: Some(VestingSchedule {
+                        start: 10,
+                        cliff: 12,
+                        duration: 60,
+                    }),
+                    ..Default::default()
+                }),
             )
             .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+        advance_to(5);
+        contract.make_vote(String::from("Doll"), true).ok();
         assert_eq!(
-            contract.get_all_projects(),
-            Ok(vec![String::from("Doll"), String::from("Toy car")])
+            contract.make_vesting_vote(String::from("Doll"), true),
+            Err(Error::BudgetNotClaimedYet)
         );
 
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::VestingStarted(event)) => assert_eq!(event.amount, 100),
+            _ => panic!("expected VestingStarted event"),
+        }
+
+        // nothing vests before the cliff
+        advance_to(18);
+        assert_eq!(contract.get_vested_amount(String::from("Doll")), Ok(0));
         assert_eq!(
-            contract.get_project_info(String::from("Doll")),
-            Ok(ProjectInfo {
-                description: String::from("I want a doll."),
-                author: accs.alice,
-                create_time: 0,
-                deadline: 5,
-                goal: 10,
-            })
+            contract.release_vested(String::from("Doll")),
+            Err(Error::NoFundsToClaim)
         );
 
+        advance_to(40);
+        assert_eq!(contract.get_vested_amount(String::from("Doll")), Ok(53));
+        assert_eq!(contract.release_vested(String::from("Doll")), Ok(()));
+        match recorded_events().last() {
+            Some(Event::BudgetClaimed(event)) => assert_eq!(event.amount, 53),
+            _ => panic!("expected BudgetClaimed event"),
+        }
+
+        advance_to(100);
+        assert_eq!(contract.release_vested(String::from("Doll")), Ok(()));
+        assert_eq!(contract.get_vesting_released(String::from("Doll")), Ok(100));
         assert_eq!(
-            contract.get_project_info(String::from("Toy car")),
-            Ok(ProjectInfo {
-                description: String::from("I want a toy car."),
-                author: accs.bob,
-                create_time: 0,
-                deadline: 6,
-                goal: 12,
-            })
+            contract.release_vested(String::from("Doll")),
+            Err(Error::NoFundsToClaim)
         );
     }
 
     #[ink::test]
-    fn test_donation_balances() {
+    fn test_vesting_cancelled() {
         let accs = test::default_accounts::<DefaultEnvironment>();
         test::set_caller::<DefaultEnvironment>(accs.alice);
         let mut contract = Crowdfund::new(3, 0, accs.alice);
@@ -596,55 +8753,81 @@ mod tests {
             .create_project(
                 String::from("Doll"),
                 String::from("I want a doll."),
-                1000,
-                500,
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    vesting: Some(VestingSchedule {
+                        start: 10,
+                        cliff: 12,
+                        duration: 60,
+                    }),
+                    ..Default::default()
+                }),
             )
             .ok();
 
+        for (donor, amount) in [(accs.bob, 60), (accs.charlie, 40)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+        advance_to(5);
         test::set_caller::<DefaultEnvironment>(accs.bob);
-        contract
-            .create_project(
-                String::from("Toy car"),
-                String::from("I want a toy car."),
-                1200,
-                600,
-            )
-            .ok();
-
-        test::set_caller::<DefaultEnvironment>(accs.charlie);
-        test::set_value_transferred::<DefaultEnvironment>(350);
-        contract.make_donation(String::from("Doll")).ok();
+        contract.make_vote(String::from("Doll"), true).ok();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.claim_budget(String::from("Doll")).ok();
 
-        test::set_caller::<DefaultEnvironment>(accs.django);
-        test::set_value_transferred::<DefaultEnvironment>(450);
-        contract.make_donation(String::from("Toy car")).ok();
+        advance_to(22);
+        contract.release_vested(String::from("Doll")).ok();
 
+        // a minority can't stop the stream
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
         assert_eq!(
-            contract.get_donated_amount(String::from("Doll"), accs.charlie),
-            Ok(350)
+            contract.make_vesting_vote(String::from("Doll"), true),
+            Ok(())
         );
         assert_eq!(
-            contract.get_donated_amount(String::from("Toy car"), accs.django),
-            Ok(450)
+            contract.refund_donation(String::from("Doll")),
+            Err(Error::CampaignSuccessfulNoRefunds)
         );
+        test::set_caller::<DefaultEnvironment>(accs.bob);
         assert_eq!(
-            contract.get_donated_amount(String::from("Toy car"), accs.charlie),
-            Ok(0)
+            contract.make_vesting_vote(String::from("Doll"), true),
+            Ok(())
         );
+        match recorded_events().last() {
+            Some(Event::VestingCancelled(event)) => assert_eq!(event.unvested, 77),
+            _ => panic!("expected VestingCancelled event"),
+        }
         assert_eq!(
-            contract.get_donated_amount(String::from("Doll"), accs.django),
-            Ok(0)
+            contract.make_vesting_vote(String::from("Doll"), false),
+            Err(Error::VestingCancelled)
         );
 
-        assert_eq!(contract.get_collected_budget(String::from("Doll")), Ok(350));
+        advance_to(100);
+        assert_eq!(contract.get_vested_amount(String::from("Doll")), Ok(23));
+        test::set_caller::<DefaultEnvironment>(accs.alice);
         assert_eq!(
-            contract.get_collected_budget(String::from("Toy car")),
-            Ok(450)
+            contract.release_vested(String::from("Doll")),
+            Err(Error::NoFundsToClaim)
         );
+
+        for donor in [accs.bob, accs.charlie].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        }
+        let refunds: Vec<(AccountId, u128)> = recorded_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::RefundIssued(event) => Some((event.donor, event.amount)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(refunds, vec![(accs.bob, 46), (accs.charlie, 30)]);
     }
 
     #[ink::test]
-    fn test_goal_not_reached() {
+    fn test_vesting_vote_quorum() {
         let accs = test::default_accounts::<DefaultEnvironment>();
         test::set_caller::<DefaultEnvironment>(accs.alice);
         let mut contract = Crowdfund::new(3, 0, accs.alice);
@@ -653,41 +8836,302 @@ mod tests {
                 String::from("Doll"),
                 String::from("I want a doll."),
                 5,
-                1000,
+                100,
+                Box::new(ProjectOptions {
+                    voting_rules: Some(VotingRules {
+                        threshold: 50,
+                        quorum: 80,
+                        default_outcome: true,
+                    }),
+                    vesting: Some(VestingSchedule {
+                        start: 10,
+                        cliff: 0,
+                        duration: 60,
+                    }),
+                    ..Default::default()
+                }),
             )
-            .ok(); // deadline = 5
+            .ok();
 
+        for (donor, amount) in [(accs.bob, 60), (accs.charlie, 40)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+        advance_to(5);
         test::set_caller::<DefaultEnvironment>(accs.bob);
-        test::set_value_transferred::<DefaultEnvironment>(350);
-        contract.make_donation(String::from("Doll")).ok();
+        contract.make_vote(String::from("Doll"), true).ok();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
 
-        // advance blocks until the deadline passes
-        loop {
-            let t = block_timestamp::<DefaultEnvironment>();
-            if t >= 5 {
-                break;
-            }
-            test::advance_block::<DefaultEnvironment>();
+        // the default outcome doesn't stop the stream without the quorum
+        advance_to(22);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        assert_eq!(
+            contract.make_vesting_vote(String::from("Doll"), true),
+            Ok(())
+        );
+        assert!(!recorded_events()
+            .iter()
+            .any(|event| matches!(event, Event::VestingCancelled(_))));
+
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(
+            contract.make_vesting_vote(String::from("Doll"), false),
+            Ok(())
+        );
+        match recorded_events().last() {
+            Some(Event::VestingCancelled(event)) => assert_eq!(event.unvested, 77),
+            _ => panic!("expected VestingCancelled event"),
         }
+    }
 
+    #[ink::test]
+    fn test_transfer_project_approvals() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
         test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    beneficiaries: co_authors(&accs),
+                    approvals_required: 3,
+                    ..Default::default()
+                }),
+            )
+            .ok();
+
+        // co-authors can't start a transfer on their own
+        test::set_caller::<DefaultEnvironment>(accs.bob);
         assert_eq!(
-            contract.claim_budget(String::from("Doll")),
-            Err(Error::GoalNotReached)
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Err(Error::YouAreNotTheFather)
+        );
+        assert_eq!(
+            contract.get_action_approvals(
+                String::from("Doll"),
+                AuthorAction::TransferProject(accs.bob)
+            ),
+            Ok(vec![])
         );
 
+        // the transfer waits for the other co-authors
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Ok(())
+        );
+        assert_eq!(contract.get_pending_author(String::from("Doll")), Ok(None));
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Ok(())
+        );
         test::set_caller::<DefaultEnvironment>(accs.bob);
-        assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
         assert_eq!(
-            contract.get_donor_refunded(String::from("Doll"), accs.bob),
-            Ok(true)
+            contract.transfer_project(String::from("Doll"), accs.bob),
+            Ok(())
         );
         assert_eq!(
-            contract.refund_donation(String::from("Doll")),
-            Err(Error::NoFundsToRefund)
+            contract.get_pending_author(String::from("Doll")),
+            Ok(Some(accs.bob))
+        );
+
+        // bob and charlie are the only co-authors left
+        assert_eq!(contract.accept_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract
+                .get_project_info(String::from("Doll"))
+                .unwrap()
+                .approvals_required,
+            2
+        );
+        assert_eq!(
+            contract.get_action_approvals(String::from("Doll"), AuthorAction::CancelProject),
+            Ok(vec![])
+        );
+
+        // the previous author's approval no longer counts
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_project_cancelled(String::from("Doll")),
+            Ok(false)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(
+            contract.cancel_project(String::from("Doll")),
+            Err(Error::YouAreNotTheFather)
+        );
+        test::set_caller::<DefaultEnvironment>(accs.charlie);
+        assert_eq!(contract.cancel_project(String::from("Doll")), Ok(()));
+        assert_eq!(
+            contract.get_project_cancelled(String::from("Doll")),
+            Ok(true)
         );
     }
 
+    #[ink::test]
+    fn test_withdraw_pool() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        matching_round(&mut contract, &accs);
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        assert_eq!(contract.withdraw_pool(0), Err(Error::RoundNotFinalised));
+        advance_to(10);
+        contract.finalise_round(0, 4).ok();
+        assert_eq!(contract.withdraw_pool(0), Err(Error::NoFundsToClaim));
+
+        advance_to(12);
+        for donor in [accs.bob, accs.charlie, accs.django].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            contract.make_vote(String::from("Kite"), false).ok();
+        }
+        contract.return_matching(String::from("Kite")).ok();
+        assert_eq!(contract.withdraw_pool(0), Err(Error::NotOwner));
+
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.pause(false).ok();
+        assert_eq!(contract.withdraw_pool(0), Err(Error::ContractPaused));
+        contract.unpause().ok();
+        assert_eq!(contract.withdraw_pool(0), Ok(()));
+        assert_eq!(contract.get_round_pool(0), Ok(0));
+        match recorded_events().last() {
+            Some(Event::PoolWithdrawn(event)) => assert_eq!(
+                event,
+                &PoolWithdrawn {
+                    round: 0,
+                    owner: accs.alice,
+                    amount: 180,
+                }
+            ),
+            _ => panic!("expected PoolWithdrawn event"),
+        }
+    }
+
+    #[ink::test]
+    fn test_vesting_starts_with_claim() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                5,
+                100,
+                Box::new(ProjectOptions {
+                    vesting: Some(VestingSchedule {
+                        start: 6,
+                        cliff: 0,
+                        duration: 60,
+                    }),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+        contract.extend_deadline(String::from("Doll"), 30).ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.make_donation(String::from("Doll")).ok();
+        advance_to(35);
+        contract.make_vote(String::from("Doll"), true).ok();
+
+        // the extended deadline passed the scheduled start
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        advance_to(36);
+        assert_eq!(contract.claim_budget(String::from("Doll")), Ok(()));
+        assert_eq!(contract.get_vested_amount(String::from("Doll")), Ok(0));
+        advance_to(66);
+        assert_eq!(contract.get_vested_amount(String::from("Doll")), Ok(50));
+    }
+
+    #[ink::test]
+    fn test_vesting_cancelled_returns_matching() {
+        let accs = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        let mut contract = Crowdfund::new(3, 0, accs.alice);
+        contract
+            .create_project(
+                String::from("Doll"),
+                String::from("I want a doll."),
+                12,
+                10,
+                Box::new(ProjectOptions {
+                    vesting: Some(VestingSchedule {
+                        start: 24,
+                        cliff: 0,
+                        duration: 60,
+                    }),
+                    ..Default::default()
+                }),
+            )
+            .ok();
+        contract
+            .create_round(0, 10, None, vec![String::from("Doll")])
+            .ok();
+
+        test::set_caller::<DefaultEnvironment>(accs.frank);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        contract.fund_round(0).ok();
+        for (donor, amount) in [(accs.bob, 60), (accs.charlie, 40)].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            test::set_value_transferred::<DefaultEnvironment>(*amount);
+            contract.make_donation(String::from("Doll")).ok();
+        }
+
+        advance_to(10);
+        contract.finalise_round(0, 2).ok();
+        advance_to(12);
+        test::set_caller::<DefaultEnvironment>(accs.bob);
+        contract.make_vote(String::from("Doll"), true).ok();
+        test::set_caller::<DefaultEnvironment>(accs.alice);
+        contract.claim_budget(String::from("Doll")).ok();
+        match recorded_events().last() {
+            Some(Event::VestingStarted(event)) => assert_eq!(event.amount, 200),
+            _ => panic!("expected VestingStarted event"),
+        }
+
+        // half of the stream vested, half of what's left was matched
+        advance_to(54);
+        for donor in [accs.charlie, accs.bob].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            contract.make_vesting_vote(String::from("Doll"), true).ok();
+        }
+        match recorded_events().last() {
+            Some(Event::MatchingReturned(event)) => assert_eq!(
+                event,
+                &MatchingReturned {
+                    project_name: String::from("Doll"),
+                    round: 0,
+                    amount: 50,
+                }
+            ),
+            _ => panic!("expected MatchingReturned event"),
+        }
+        assert_eq!(contract.get_round_pool(0), Ok(50));
+
+        for donor in [accs.bob, accs.charlie].iter() {
+            test::set_caller::<DefaultEnvironment>(*donor);
+            assert_eq!(contract.refund_donation(String::from("Doll")), Ok(()));
+        }
+        let refunds: Vec<(AccountId, u128)> = recorded_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::RefundIssued(event) => Some((event.donor, event.amount)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(refunds, vec![(accs.bob, 30), (accs.charlie, 20)]);
+    }
+
     macro_rules! voting_tests {
         ($($name:ident: $final_vote:expr,)*) => {
         $(
@@ -696,7 +9140,7 @@ mod tests {
                 let accs = test::default_accounts::<DefaultEnvironment>();
                 test::set_caller::<DefaultEnvironment>(accs.alice);
                 let mut contract = Crowdfund::new(3, 0, accs.alice);
-                contract.create_project(String::from("Doll"), String::from("I want a doll."), 5, 1000).ok(); // deadline = 5
+                contract.create_project(String::from("Doll"), String::from("I want a doll."), 5, 1000, Box::default()).ok(); // deadline = 5
 
                 test::set_caller::<DefaultEnvironment>(accs.bob);
                 test::set_value_transferred::<DefaultEnvironment>(499); // donate 499
@@ -749,6 +9193,8 @@ mod tests {
                         assert_eq!(contract.get_voting_state(String::from("Doll")), Ok(ProjectVotes {
                             ovr_voted_yes: 500 + vote as u128,
                             ovr_voted_no: 499 + (1 - vote as u128),
+                            quad_voted_yes: 0,
+                            quad_voted_no: 0,
                         }));
                     },
                     None => {
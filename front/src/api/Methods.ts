@@ -47,6 +47,31 @@ type Handler<T> = {
 
 const contractAddress = process.env.REACT_APP_CONTRACT_ADDRESS!
 
+// Largest page of project names the contract returns at once.
+const PAGE_SIZE = 50
+
+// Projects created from the app use the platform defaults for every option.
+const DEFAULT_PROJECT_OPTIONS = {
+    milestones: [],
+    token: null,
+    rewardTiers: [],
+    rewardCollection: null,
+    withdrawalLockIn: null,
+    votingRules: null,
+    quadraticVoting: false,
+    fundingMode: "AllOrNothing",
+    stretchGoals: [],
+    stretchGoalVotes: false,
+    maxBudget: null,
+    refundExcess: false,
+    minDonation: null,
+    maxDonationPerAccount: null,
+    private: false,
+    beneficiaries: [],
+    approvalsRequired: 0,
+    vesting: null,
+}
+
 export class API {
     private readonly api: ApiPromise
     private readonly contract: ContractPromise
@@ -148,12 +173,27 @@ export class API {
     }
 
     async getAllProjects(): Promise<string[]> {
-        const outcome = await this.contract.query.getAllProjects(
+        const countOutcome = await this.contract.query.getProjectCount(
             this.originAccount.account.address,
             this.options
         )
+        const count = getResult<number>(countOutcome)
 
-        return getResult(outcome)
+        // The contract returns the names a page at a time.
+        const projects: string[] = []
+        while (projects.length < count) {
+            const outcome = await this.contract.query.getProjects(
+                this.originAccount.account.address,
+                this.options,
+                projects.length,
+                PAGE_SIZE
+            )
+            const page = getResult<string[]>(outcome)
+            if (page.length === 0) break
+            projects.push(...page)
+        }
+
+        return projects
     }
 
     async getDonatedAmount(
@@ -245,7 +285,8 @@ export class API {
             projectName,
             description,
             Date.parse(deadline),
-            goal
+            goal,
+            DEFAULT_PROJECT_OPTIONS
         )
 
         getResult<void>(outcome)
@@ -255,7 +296,8 @@ export class API {
             projectName,
             description,
             Date.parse(deadline),
-            goal
+            goal,
+            DEFAULT_PROJECT_OPTIONS
         )
 
         await this.signAndSend(tx, handler)